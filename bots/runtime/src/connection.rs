@@ -1,10 +1,26 @@
 use std::net::TcpStream;
 
-use packets::{ClientPacket, ServerPacket};
+use packets::{
+    ClientPacket, HelloPacket, PacketError, ServerPacket, encoding::Encoding,
+    version::PROTOCOL_VERSION,
+};
+use thiserror::Error;
 use tungstenite::{Message, WebSocket, connect, stream::MaybeTlsStream};
 
+/// binary frames are smaller and faster to parse for many concurrent games
+const ENCODING: Encoding = Encoding::MessagePack;
+
+#[derive(Error, Debug)]
+pub enum ConnectionError {
+    #[error(transparent)]
+    Socket(#[from] tungstenite::Error),
+    #[error(transparent)]
+    Packet(#[from] PacketError),
+}
+
 pub struct Connection {
     socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    encoding: Encoding,
 }
 
 impl Connection {
@@ -16,21 +32,51 @@ impl Connection {
         ))
         .expect("Can't connect");
 
-        Connection { socket }
+        let mut connection = Connection {
+            socket,
+            encoding: Encoding::Text,
+        };
+        connection.hello();
+        connection
+    }
+
+    fn hello(&mut self) {
+        self.send(ClientPacket::Hello(HelloPacket {
+            protocol_version: PROTOCOL_VERSION,
+            client_name: Some("runtime".to_string()),
+            encoding: ENCODING,
+        }));
+
+        match self.receive().expect("Can't receive welcome") {
+            ServerPacket::Welcome(packet) if packet.protocol_version == PROTOCOL_VERSION => {
+                self.encoding = packet.encoding;
+            }
+            ServerPacket::Welcome(packet) => panic!(
+                "server only speaks protocol version {}",
+                packet.protocol_version
+            ),
+            ServerPacket::Error(packet) => panic!("server rejected protocol: {}", packet.message),
+            _ => panic!("Expected Welcome packet"),
+        }
     }
 
     pub fn send(&mut self, packet: ClientPacket) {
-        self.socket
-            .send(Message::Text(packet.to_string().into()))
-            .unwrap();
+        let msg = match self.encoding {
+            Encoding::Text => Message::Text(packet.to_text().unwrap().into()),
+            encoding => Message::Binary(packet.to_binary(encoding).unwrap().into()),
+        };
+        self.socket.send(msg).unwrap();
     }
 
-    pub fn receive(&mut self) -> ServerPacket {
+    /// the server switches to the negotiated encoding with `welcome`
+    pub fn receive(&mut self) -> Result<ServerPacket, ConnectionError> {
         loop {
-            let msg = self.socket.read().unwrap();
-
-            if let Message::Text(text) = msg {
-                return ServerPacket::from_string(text.as_ref()).unwrap();
+            match self.socket.read()? {
+                Message::Text(text) => return Ok(ServerPacket::from_string(text.as_ref())?),
+                Message::Binary(bytes) => {
+                    return Ok(ServerPacket::from_binary(&bytes, self.encoding)?);
+                }
+                _ => {}
             }
         }
     }
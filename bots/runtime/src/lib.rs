@@ -5,8 +5,11 @@ use game::{
     data::{Connection, Round, Station},
     event::{DetectiveActionType, GameState, MisterXActionType, Role},
 };
-use packets::{ClientPacket, JoinGamePacket, ServerPacket};
+use packets::{ClientPacket, JoinGamePacket, LobbyReference, ServerPacket};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::connection::ConnectionError;
 
 pub mod connection;
 
@@ -59,7 +62,7 @@ pub struct MisterXMove {
 
 #[derive(Debug)]
 pub struct DetectiveAction {
-    pub moves: [Option<DetectiveMove>; 4],
+    pub moves: Vec<Option<DetectiveMove>>,
 }
 
 #[derive(Debug)]
@@ -69,6 +72,40 @@ pub struct DetectiveMove {
     pub action_type: DetectiveActionType,
 }
 
+impl MisterXAction {
+    pub fn into_packet(self) -> ClientPacket {
+        let mut moves = vec![packets::MoveMisterXPacket {
+            station_id: self.first_move.station,
+            transport_type: self.first_move.action_type,
+        }];
+
+        if let Some(second) = self.second_move {
+            moves.push(packets::MoveMisterXPacket {
+                station_id: second.station,
+                transport_type: second.action_type,
+            });
+        }
+
+        ClientPacket::MoveMisterX(moves)
+    }
+}
+
+impl DetectiveAction {
+    pub fn into_packets(self) -> Vec<ClientPacket> {
+        self.moves
+            .into_iter()
+            .flatten()
+            .map(|action| {
+                ClientPacket::MoveDetective(packets::MoveDetectivePacket {
+                    color: action.color,
+                    station_id: action.station,
+                    transport_type: action.action_type,
+                })
+            })
+            .collect()
+    }
+}
+
 pub trait Bot {
     fn new(data: GameData) -> Self;
     fn next_mister_x_move(&mut self, game_state: &GameState) -> MisterXAction;
@@ -82,7 +119,7 @@ struct Args {
     #[arg(short, long)]
     server: String,
 
-    /// UUID of the game to join
+    /// UUID or join code of the game to join
     #[arg(short, long)]
     game_id: String,
 
@@ -103,12 +140,12 @@ pub fn run_from_cli<B: Bot>() {
 
     let mut connection = connection::Connection::new(&args.server);
 
-    let role = join_game(&mut connection, args.game_id);
+    let role = join_game(&mut connection, args.game_id).expect("Can't join the game");
     if args.simple_output.not() {
         println!("game stared: playing as {:?}", role);
     }
 
-    let winner = play_game(&mut bot, &mut connection, &role);
+    let winner = play_game(&mut bot, &mut connection, &role).expect("Connection failed");
     if args.simple_output {
         println!(
             "{}",
@@ -123,69 +160,59 @@ pub fn run_from_cli<B: Bot>() {
     }
 }
 
-fn join_game(connection: &mut connection::Connection, game_id: String) -> Role {
+fn join_game(
+    connection: &mut connection::Connection,
+    game_id: String,
+) -> Result<Role, ConnectionError> {
     connection.send(ClientPacket::JoinGame(JoinGamePacket {
-        id: game_id.try_into().unwrap(),
+        id: match Uuid::try_parse(&game_id) {
+            Ok(id) => LobbyReference::Id(id),
+            Err(_) => LobbyReference::Code(game_id),
+        },
+        legal_moves: false,
+        state_deltas: false,
     }));
 
     connection.send(ClientPacket::StartGame);
 
     loop {
-        if let ServerPacket::GameStarted(packet) = connection.receive() {
-            return packet.role;
+        if let ServerPacket::GameStarted(packet) = connection.receive()? {
+            return Ok(packet.role);
         }
     }
 }
 
-fn play_game<B: Bot>(bot: &mut B, connection: &mut connection::Connection, role: &Role) -> Role {
+fn play_game<B: Bot>(
+    bot: &mut B,
+    connection: &mut connection::Connection,
+    role: &Role,
+) -> Result<Role, ConnectionError> {
     loop {
-        match connection.receive() {
+        match connection.receive()? {
             ServerPacket::StartMove(packet) => {
                 if packet.role.eq(role).not() {
                     continue;
                 }
 
-                let ServerPacket::GameState(state) = connection.receive() else {
+                let ServerPacket::GameState(state) = connection.receive()? else {
                     panic!("Expected GameState packet");
                 };
 
                 match role {
                     Role::Detective => {
-                        let action = bot.next_detective_move(&state);
-
-                        for action in action.moves.into_iter().flatten() {
-                            connection.send(ClientPacket::MoveDetective(
-                                packets::MoveDetectivePacket {
-                                    color: action.color,
-                                    station_id: action.station,
-                                    transport_type: action.action_type,
-                                },
-                            ));
+                        for packet in bot.next_detective_move(&state).into_packets() {
+                            connection.send(packet);
                         }
                     }
                     Role::MisterX => {
-                        let action = bot.next_mister_x_move(&state);
-
-                        let mut moves = vec![packets::MoveMisterXPacket {
-                            station_id: action.first_move.station,
-                            transport_type: action.first_move.action_type,
-                        }];
-
-                        if let Some(second) = action.second_move {
-                            moves.push(packets::MoveMisterXPacket {
-                                station_id: second.station,
-                                transport_type: second.action_type,
-                            });
-                        }
-
-                        connection.send(ClientPacket::MoveMisterX(moves));
+                        connection.send(bot.next_mister_x_move(&state).into_packet());
                     }
                 }
 
                 connection.send(ClientPacket::SubmitMove);
             }
             ServerPacket::GameEnded(packet) => {
-                return packet.winner;
+                return Ok(packet.winner);
             }
             _ => {}
         }
@@ -81,11 +81,12 @@ fn game_id(server: &str) -> Option<Uuid> {
 
     connection.send(ClientPacket::CreateGame(packets::CreateGamePacket {
         number_of_detectives: 4,
+        spectators: packets::SpectatorSettings::default(),
+        public: false,
+        map: None,
     }));
 
-    let msg = connection.receive();
-
-    if let ServerPacket::Game(game) = msg {
+    if let Ok(ServerPacket::Game(game)) = connection.receive() {
         Some(game.id)
     } else {
         None
@@ -38,6 +38,9 @@ impl Root {
             self.ws_connection
                 .send(ClientPacket::CreateGame(packets::CreateGamePacket {
                     number_of_detectives: 4,
+                    spectators: packets::SpectatorSettings::default(),
+                    public: false,
+                    map: None,
                 }))
         {
             eprintln!("Failed to create game: {}", err);
@@ -63,7 +66,11 @@ impl Root {
 
         if let Err(err) = self
             .ws_connection
-            .send(ClientPacket::JoinGame(packets::JoinGamePacket { id }))
+            .send(ClientPacket::JoinGame(packets::JoinGamePacket {
+                id: packets::LobbyReference::Id(id),
+                legal_moves: false,
+                state_deltas: false,
+            }))
         {
             eprintln!("Failed to join game: {}", err);
         }
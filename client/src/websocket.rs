@@ -1,6 +1,6 @@
 use std::net::TcpStream;
 
-use packets::{ClientPacket, ServerPacket};
+use packets::{ClientPacket, PacketError, ServerPacket};
 use thiserror::Error;
 use tungstenite::{Message, WebSocket, connect, stream::MaybeTlsStream};
 
@@ -8,6 +8,8 @@ use tungstenite::{Message, WebSocket, connect, stream::MaybeTlsStream};
 pub enum ConnectionError {
     #[error("failed to connect")]
     FailedToConnect,
+    #[error(transparent)]
+    Packet(#[from] PacketError),
 }
 
 pub struct Connection {
@@ -45,9 +47,8 @@ impl Connection {
 
     pub fn send(&mut self, packet: ClientPacket) -> Result<(), ConnectionError> {
         let socket = self.try_get_connection()?;
-        socket
-            .send(Message::Text(packet.to_string().into()))
-            .unwrap();
+        let text = packet.to_text()?;
+        socket.send(Message::Text(text.into())).unwrap();
 
         Ok(())
     }
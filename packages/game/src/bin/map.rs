@@ -0,0 +1,137 @@
+//! tools for map authors
+//!
+//! `map check <map.toml>...`
+//! lists every problem of the maps, the server refuses to load maps with problems
+//!
+//! `map convert <stations.txt> <connections.txt> [--detectives 1,2,...] [--mister-x 3,4,...]`
+//! prints the old text format as a map file, start pools default to every station
+//!
+//! `map render <map.toml> [--dot]`
+//! prints the map as SVG or as a Graphviz graph
+
+use std::{fs, process};
+
+use game::map::{
+    MapFile, Rules, StartPools,
+    render::{self, Overlay},
+};
+
+const USAGE: &str = "usage:
+  map check <map.toml>...
+  map convert <stations.txt> <connections.txt> [--detectives 1,2,...] [--mister-x 3,4,...]
+  map render <map.toml> [--dot]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("check") => check(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("render") => render(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        process::exit(1);
+    }
+}
+
+fn check(paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("missing path to a map".to_string());
+    }
+
+    let mut failed = false;
+    for path in paths {
+        let problems = match fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| MapFile::parse_unchecked(&content).map_err(|err| err.to_string()))
+        {
+            Ok(map) => map.check().iter().map(ToString::to_string).collect(),
+            Err(err) => vec![err],
+        };
+
+        if problems.is_empty() {
+            println!("{path}: ok");
+        }
+        for problem in &problems {
+            println!("{path}: {problem}");
+        }
+        failed |= !problems.is_empty();
+    }
+
+    if failed {
+        return Err("some maps have problems".to_string());
+    }
+
+    Ok(())
+}
+
+fn convert(args: &[String]) -> Result<(), String> {
+    let [stations_path, connections_path, options @ ..] = args else {
+        return Err("missing path to stations.txt or connections.txt".to_string());
+    };
+
+    let read = |path: &String| fs::read_to_string(path).map_err(|err| format!("{path}: {err}"));
+    let stations = read(stations_path)?;
+    let connections = read(connections_path)?;
+
+    let mut detectives = None;
+    let mut mister_x = None;
+    for option in options.chunks(2) {
+        let [name, value] = option else {
+            return Err(format!("missing value for {}", option[0]));
+        };
+        let pool = value
+            .split(',')
+            .map(|id| id.parse().map_err(|_| format!("invalid station {id}")))
+            .collect::<Result<Vec<u8>, _>>()?;
+
+        match name.as_str() {
+            "--detectives" => detectives = Some(pool),
+            "--mister-x" => mister_x = Some(pool),
+            _ => return Err(format!("unknown option {name}")),
+        }
+    }
+
+    let mut map = MapFile::from_txt(
+        &stations,
+        &connections,
+        Rules::default(),
+        StartPools {
+            detectives: vec![],
+            mister_x: vec![],
+        },
+    )
+    .map_err(|err| err.to_string())?;
+
+    let all_stations: Vec<u8> = map.stations.iter().map(|station| station.id).collect();
+    map.start.detectives = detectives.unwrap_or_else(|| all_stations.clone());
+    map.start.mister_x = mister_x.unwrap_or(all_stations);
+
+    print!("{}", map.to_toml());
+
+    Ok(())
+}
+
+fn render(args: &[String]) -> Result<(), String> {
+    let (path, dot) = match args {
+        [path] => (path, false),
+        [path, option] if option == "--dot" => (path, true),
+        _ => return Err("missing path to a map".to_string()),
+    };
+
+    let content = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    let map = MapFile::parse_unchecked(&content).map_err(|err| format!("{path}: {err}"))?;
+
+    let overlay = Overlay::default();
+    let output = if dot {
+        render::to_dot(&map.stations, &map.connections, &overlay)
+    } else {
+        render::to_svg(&map.stations, &map.connections, &overlay)
+    };
+    print!("{output}");
+
+    Ok(())
+}
@@ -1,12 +1,12 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     character::{ActionTypeTrait, Character},
     data::StationType,
-    event::DetectiveActionType,
+    event::{DetectiveActionType, DetectiveTransportData},
 };
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Action {
     pub station: u8,
     pub action_type: DetectiveActionType,
@@ -23,6 +23,7 @@ impl ActionTypeTrait for DetectiveActionType {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Detective {
     color: String,
     start_station_id: u8,
@@ -93,9 +94,11 @@ impl Detective {
             .actions
             .iter()
             .filter(|step| matches!(step.action_type, DetectiveActionType::Taxi))
-            .count() as u8;
+            .count()
+            .try_into()
+            .unwrap_or(u8::MAX);
 
-        10 - count
+        DetectiveTransportData::default().taxi.saturating_sub(count)
     }
 
     pub fn bus(&self) -> u8 {
@@ -103,9 +106,11 @@ impl Detective {
             .actions
             .iter()
             .filter(|step| matches!(step.action_type, DetectiveActionType::Bus))
-            .count() as u8;
+            .count()
+            .try_into()
+            .unwrap_or(u8::MAX);
 
-        8 - count
+        DetectiveTransportData::default().bus.saturating_sub(count)
     }
 
     pub fn underground(&self) -> u8 {
@@ -113,8 +118,12 @@ impl Detective {
             .actions
             .iter()
             .filter(|step| matches!(step.action_type, DetectiveActionType::Underground))
-            .count() as u8;
+            .count()
+            .try_into()
+            .unwrap_or(u8::MAX);
 
-        4 - count
+        DetectiveTransportData::default()
+            .underground
+            .saturating_sub(count)
     }
 }
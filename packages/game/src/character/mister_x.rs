@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     character::{ActionTypeTrait, Character},
@@ -6,13 +6,13 @@ use crate::{
     event::MisterXActionType,
 };
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveData {
     pub station: u8,
     pub action_type: MisterXActionType,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Action {
     Single(MoveData),
@@ -34,6 +34,7 @@ impl ActionTypeTrait for MisterXActionType {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MisterX {
     start_station_id: u8,
     actions: Vec<Action>,
@@ -109,9 +110,11 @@ impl MisterX {
             .action_types()
             .into_iter()
             .filter(|step| step.eq(&MisterXActionType::Hidden))
-            .count() as u8;
+            .count()
+            .try_into()
+            .unwrap_or(u8::MAX);
 
-        5 - count
+        5_u8.saturating_sub(count)
     }
 
     /// Returns number of aviable double moves
@@ -120,8 +123,10 @@ impl MisterX {
             .actions
             .iter()
             .filter(|step| matches!(step, Action::Double(_, _)))
-            .count() as u8;
+            .count()
+            .try_into()
+            .unwrap_or(u8::MAX);
 
-        2 - count
+        2_u8.saturating_sub(count)
     }
 }
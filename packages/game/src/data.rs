@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "typescript")]
+use ts_rs::TS;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Round {
@@ -7,13 +9,15 @@ pub struct Round {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct Connection {
     pub from: u8,
     pub to: u8,
     pub mode: StationType,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(TS))]
 #[serde(rename_all = "snake_case")]
 pub enum StationType {
     Taxi,
@@ -23,9 +27,12 @@ pub enum StationType {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct Station {
     pub id: u8,
     pub pos_x: u32,
     pub pos_y: u32,
     pub types: Vec<StationType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
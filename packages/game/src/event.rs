@@ -1,8 +1,14 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "typescript")]
+use ts_rs::TS;
 
 use crate::replay::Replay;
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 #[serde(rename_all = "snake_case")]
 pub enum Role {
     Detective,
@@ -10,6 +16,8 @@ pub enum Role {
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 #[serde(rename_all = "snake_case")]
 pub enum MisterXActionType {
     Taxi,
@@ -19,6 +27,8 @@ pub enum MisterXActionType {
 }
 
 #[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 #[serde(rename_all = "snake_case")]
 pub enum DetectiveActionType {
     Taxi,
@@ -27,44 +37,189 @@ pub enum DetectiveActionType {
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct DetectiveData {
     pub color: String,
     pub station_id: u8,
     pub available_transport: DetectiveTransportData,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct DetectiveTransportData {
     pub taxi: u8,
     pub bus: u8,
     pub underground: u8,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+/// tickets of a detective at the start of a game
+impl Default for DetectiveTransportData {
+    fn default() -> Self {
+        Self {
+            taxi: 10,
+            bus: 8,
+            underground: 4,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct MisterXData {
     pub station_id: Option<u8>,
     pub abilities: MisterXAbilityData,
     pub moves: Vec<MisterXActionType>,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct MisterXAbilityData {
     pub double_move: u8,
     pub hidden: u8,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct GameState {
     pub players: Vec<DetectiveData>,
     pub mister_x: MisterXData,
     pub round: u8,
+    /// counts the states sent in this game, a delta has the `seq` of the state it leads to
+    #[serde(default)]
+    pub seq: u32,
+}
+
+/// changes from the last state sent to the next one
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct GameStateDelta {
+    pub seq: u32,
+    pub changes: Vec<StateChange>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum StateChange {
+    PieceMoved {
+        color: String,
+        station_id: u8,
+    },
+    TicketsChanged {
+        color: String,
+        available_transport: DetectiveTransportData,
+    },
+    RoundAdvanced {
+        round: u8,
+    },
+}
+
+impl GameStateDelta {
+    /// none if the states differ in more than detectives and the round, e.g. when Mister X moved
+    pub fn between(before: &GameState, after: &GameState) -> Option<Self> {
+        if before.players.len() != after.players.len() || before.mister_x != after.mister_x {
+            return None;
+        }
+
+        let mut changes = vec![];
+        for (before, after) in before.players.iter().zip(&after.players) {
+            if before.color != after.color {
+                return None;
+            }
+            if before.station_id != after.station_id {
+                changes.push(StateChange::PieceMoved {
+                    color: after.color.clone(),
+                    station_id: after.station_id,
+                });
+            }
+            if before.available_transport != after.available_transport {
+                changes.push(StateChange::TicketsChanged {
+                    color: after.color.clone(),
+                    available_transport: after.available_transport,
+                });
+            }
+        }
+        if before.round != after.round {
+            changes.push(StateChange::RoundAdvanced { round: after.round });
+        }
+
+        Some(Self {
+            seq: after.seq,
+            changes,
+        })
+    }
+
+    /// applies the changes to the state they were computed from
+    pub fn apply(&self, state: &mut GameState) {
+        for change in &self.changes {
+            match change {
+                StateChange::PieceMoved { color, station_id } => {
+                    if let Some(player) = state.players.iter_mut().find(|p| &p.color == color) {
+                        player.station_id = *station_id;
+                    }
+                }
+                StateChange::TicketsChanged {
+                    color,
+                    available_transport,
+                } => {
+                    if let Some(player) = state.players.iter_mut().find(|p| &p.color == color) {
+                        player.available_transport = *available_transport;
+                    }
+                }
+                StateChange::RoundAdvanced { round } => state.round = *round,
+            }
+        }
+        state.seq = self.seq;
+    }
+}
+
+/// a move the active side can make, the same as the `moveDetective` packet
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct DetectiveMove {
+    pub color: String,
+    pub station_id: u8,
+    pub transport_type: DetectiveActionType,
+}
+
+/// a single move Mister X can make, two of them make a double move
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct MisterXMove {
+    pub station_id: u8,
+    pub transport_type: MisterXActionType,
+}
+
+/// every move of the active side at the start of its turn
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(rename_all = "snake_case", tag = "role", content = "moves")]
+pub enum LegalMoves {
+    Detective(Vec<DetectiveMove>),
+    MisterX(Vec<MisterXMove>),
 }
 
 #[allow(async_fn_in_trait)]
 pub trait EventListener {
     async fn on_game_start(&self);
-    async fn on_start_round(&self, role: &Role);
+    async fn on_start_round(&self, role: &Role, legal_moves: &LegalMoves);
     async fn on_end_move(&self);
     async fn on_game_ended(&self, replay: &Replay);
-    async fn on_game_state_update(&self, state: GameState, show_mister_x: bool);
+    /// the delta is only set for tentative moves, turns start with the full state
+    async fn on_game_state_update(
+        &self,
+        state: GameState,
+        delta: Option<&GameStateDelta>,
+        show_mister_x: bool,
+    );
 }
@@ -1,5 +1,6 @@
 use std::ops::Not;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
@@ -10,27 +11,74 @@ use crate::{
     },
     data::{Connection, Round},
     event::{
-        DetectiveActionType, DetectiveData, DetectiveTransportData, EventListener, GameState,
-        MisterXAbilityData, MisterXActionType, MisterXData, Role,
+        DetectiveActionType, DetectiveData, DetectiveMove, DetectiveTransportData, EventListener,
+        GameState, GameStateDelta, LegalMoves, MisterXAbilityData, MisterXActionType, MisterXData,
+        MisterXMove, Role,
     },
-    map_utils::all_valid_detective_moves,
+    map_utils::{all_valid_detective_moves, legal_mister_x_moves},
     replay::Replay,
 };
 
 mod character;
 pub mod data;
 pub mod event;
+pub mod map;
 pub mod map_utils;
 pub mod replay;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum GameError {
     #[error("invalid move")]
-    InvalidMove,
+    InvalidMove(MoveError),
     #[error("not all moved")]
     NotAllMoved,
 }
 
+/// why a move was rejected
+#[derive(Error, Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveError {
+    #[error("it's not the turn of this role")]
+    NotYourTurn,
+    #[error("there is no detective with this color")]
+    UnknownDetective,
+    #[error("Mister X has to move once or twice")]
+    WrongNumberOfMoves,
+    #[error("no tickets left for this transport")]
+    NoTicketsLeft,
+    #[error("no double moves left")]
+    NoDoubleMovesLeft,
+    #[error("the stations aren't connected by this transport")]
+    NoConnection,
+}
+
+/// state of a game between two moves, used to restore it later
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    active_role: Role,
+    game_round: u8,
+
+    detectives: Vec<Detective>,
+    mister_x: MisterX,
+
+    /// the last state sent, so `seq` and deltas continue after a restore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_state: Option<GameState>,
+}
+
+impl Snapshot {
+    /// whether the snapshot can be a running game with this many rounds,
+    /// longer histories would overflow the ticket counters
+    pub fn fits(&self, rounds: usize) -> bool {
+        (self.game_round as usize) < rounds
+            && self.mister_x.actions().len() <= rounds
+            && self
+                .detectives
+                .iter()
+                .all(|detective| detective.actions().len() <= rounds)
+    }
+}
+
 pub struct Game<E: EventListener> {
     active_role: Role,
     game_round: u8,
@@ -41,6 +89,10 @@ pub struct Game<E: EventListener> {
     detectives: Vec<Detective>,
     mister_x: MisterX,
 
+    winner: Option<Role>,
+    /// deltas are computed from it
+    last_state: Option<GameState>,
+
     event_listener: E,
 }
 
@@ -62,20 +114,61 @@ impl<E: EventListener> Game<E> {
             game_round: 0,
             detectives,
             mister_x: MisterX::new(mister_x_start_station),
+            winner: None,
+            last_state: None,
+            event_listener,
+            connections,
+            rounds,
+        }
+    }
+
+    /// continues a game from a snapshot, no events are sent
+    pub fn restore(
+        snapshot: Snapshot,
+        connections: Vec<Connection>,
+        rounds: Vec<Round>,
+        event_listener: E,
+    ) -> Game<E> {
+        Game {
+            active_role: snapshot.active_role,
+            game_round: snapshot.game_round,
+            detectives: snapshot.detectives,
+            mister_x: snapshot.mister_x,
+            winner: None,
+            last_state: snapshot.last_state,
             event_listener,
             connections,
             rounds,
         }
     }
 
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            active_role: self.active_role.clone(),
+            game_round: self.game_round,
+            detectives: self.detectives.clone(),
+            mister_x: self.mister_x.clone(),
+            last_state: self.last_state.clone(),
+        }
+    }
+
     pub fn active_role(&self) -> &Role {
         &self.active_role
     }
 
+    /// the winning role once the game has ended
+    pub fn winner(&self) -> Option<&Role> {
+        self.winner.as_ref()
+    }
+
     pub fn event_listener(&self) -> &E {
         &self.event_listener
     }
 
+    pub fn event_listener_mut(&mut self) -> &mut E {
+        &mut self.event_listener
+    }
+
     pub async fn start(&mut self) {
         self.event_listener.on_game_start().await;
         self.start_move(Role::MisterX).await;
@@ -83,12 +176,31 @@ impl<E: EventListener> Game<E> {
 
     pub async fn start_move(&mut self, role: Role) {
         self.active_role = role.clone();
-        self.event_listener.on_start_round(&role).await;
-        self.send_game_state(self.should_show_mister_x()).await;
+        self.event_listener
+            .on_start_round(&role, &self.legal_moves())
+            .await;
+        self.send_game_state(self.should_show_mister_x(), false)
+            .await;
+    }
+
+    /// tentative moves send a delta to the last state along with the full state
+    async fn send_game_state(&mut self, show_mister_x: bool, tentative: bool) {
+        let mut state = self.game_state();
+        state.seq += 1;
+        let delta = match (&self.last_state, tentative) {
+            (Some(before), true) => GameStateDelta::between(before, &state),
+            _ => None,
+        };
+
+        self.event_listener
+            .on_game_state_update(state.clone(), delta.as_ref(), show_mister_x)
+            .await;
+        self.last_state = Some(state);
     }
 
-    async fn send_game_state(&self, show_mister_x: bool) {
-        let state = GameState {
+    /// full state of the game including the position of Mister X
+    pub fn game_state(&self) -> GameState {
+        GameState {
             players: self
                 .detectives
                 .iter()
@@ -111,14 +223,55 @@ impl<E: EventListener> Game<E> {
                 moves: self.mister_x.action_types(),
             },
             round: self.game_round,
-        };
+            seq: self.last_state.as_ref().map_or(0, |state| state.seq),
+        }
+    }
 
-        self.event_listener
-            .on_game_state_update(state, show_mister_x)
-            .await;
+    /// every move the active role can make, a double move consists of two of them
+    pub fn legal_moves(&self) -> LegalMoves {
+        match self.active_role {
+            Role::Detective => LegalMoves::Detective(
+                self.detectives
+                    .iter()
+                    .flat_map(|detective| {
+                        all_valid_detective_moves(
+                            &self.connections,
+                            detective.station_id(),
+                            &DetectiveTransportData {
+                                taxi: detective.taxi(),
+                                bus: detective.bus(),
+                                underground: detective.underground(),
+                            },
+                        )
+                        .into_iter()
+                        .map(|(station_id, transport_type)| DetectiveMove {
+                            color: detective.color().to_string(),
+                            station_id,
+                            transport_type,
+                        })
+                    })
+                    .collect(),
+            ),
+            Role::MisterX => LegalMoves::MisterX(
+                legal_mister_x_moves(
+                    &self.connections,
+                    self.mister_x.station_id(),
+                    &MisterXAbilityData {
+                        double_move: self.mister_x.double_moves(),
+                        hidden: self.mister_x.hidden(),
+                    },
+                )
+                .into_iter()
+                .map(|(station_id, transport_type)| MisterXMove {
+                    station_id,
+                    transport_type,
+                })
+                .collect(),
+            ),
+        }
     }
 
-    fn should_show_mister_x(&self) -> bool {
+    pub fn should_show_mister_x(&self) -> bool {
         match self.rounds.get(self.game_round as usize) {
             Some(round) => round.show_mister_x,
             None => false,
@@ -126,8 +279,11 @@ impl<E: EventListener> Game<E> {
     }
 
     pub fn move_mister_x(&mut self, moves: Vec<(u8, MisterXActionType)>) -> Result<(), GameError> {
-        if moves.len() > 2 {
-            return Err(GameError::InvalidMove);
+        if self.active_role != Role::MisterX {
+            return Err(GameError::InvalidMove(MoveError::NotYourTurn));
+        }
+        if moves.is_empty() || moves.len() > 2 {
+            return Err(GameError::InvalidMove(MoveError::WrongNumberOfMoves));
         }
 
         self.mister_x.trim_actions(self.game_round as usize);
@@ -137,14 +293,14 @@ impl<E: EventListener> Game<E> {
         match (first, second) {
             (Some(first), None) => {
                 if !self.mister_x.can_do_action(&first.1) {
-                    return Err(GameError::InvalidMove);
+                    return Err(GameError::InvalidMove(MoveError::NoTicketsLeft));
                 }
 
                 if self
                     .has_connection(self.mister_x.station_id(), first.0, &first.1)
                     .not()
                 {
-                    return Err(GameError::InvalidMove);
+                    return Err(GameError::InvalidMove(MoveError::NoConnection));
                 }
 
                 self.mister_x
@@ -156,11 +312,11 @@ impl<E: EventListener> Game<E> {
             (Some(first), Some(second)) => {
                 if !self.mister_x.can_do_action(&first.1) || !self.mister_x.can_do_action(&second.1)
                 {
-                    return Err(GameError::InvalidMove);
+                    return Err(GameError::InvalidMove(MoveError::NoTicketsLeft));
                 }
 
                 if self.mister_x.double_moves() == 0 {
-                    return Err(GameError::InvalidMove);
+                    return Err(GameError::InvalidMove(MoveError::NoDoubleMovesLeft));
                 }
 
                 if self
@@ -168,7 +324,7 @@ impl<E: EventListener> Game<E> {
                     .not()
                     || self.has_connection(first.0, second.0, &second.1).not()
                 {
-                    return Err(GameError::InvalidMove);
+                    return Err(GameError::InvalidMove(MoveError::NoConnection));
                 }
 
                 self.mister_x.add_action(mister_x::Action::Double(
@@ -182,7 +338,7 @@ impl<E: EventListener> Game<E> {
                     },
                 ));
             }
-            _ => return Err(GameError::InvalidMove),
+            _ => unreachable!("number of moves was checked"),
         }
 
         Ok(())
@@ -194,16 +350,20 @@ impl<E: EventListener> Game<E> {
         station_id: u8,
         transport_type: DetectiveActionType,
     ) -> Result<(), GameError> {
+        if self.active_role != Role::Detective {
+            return Err(GameError::InvalidMove(MoveError::NotYourTurn));
+        }
+
         let detective = self
             .detectives
             .iter_mut()
             .find(|detective| detective.color() == color)
-            .unwrap();
+            .ok_or(GameError::InvalidMove(MoveError::UnknownDetective))?;
 
         detective.trim_actions(self.game_round as usize);
 
         if !detective.can_do_action(&transport_type) {
-            return Err(GameError::InvalidMove);
+            return Err(GameError::InvalidMove(MoveError::NoTicketsLeft));
         }
 
         let detective_station = detective.station_id();
@@ -211,7 +371,7 @@ impl<E: EventListener> Game<E> {
             .has_connection(detective_station, station_id, &transport_type)
             .not()
         {
-            return Err(GameError::InvalidMove);
+            return Err(GameError::InvalidMove(MoveError::NoConnection));
         }
 
         let detective = self
@@ -225,7 +385,8 @@ impl<E: EventListener> Game<E> {
             action_type: transport_type,
         });
 
-        self.send_game_state(self.should_show_mister_x()).await;
+        self.send_game_state(self.should_show_mister_x(), true)
+            .await;
 
         Ok(())
     }
@@ -287,6 +448,8 @@ impl<E: EventListener> Game<E> {
     }
 
     pub async fn end_game(&mut self, winner: Role) {
+        self.winner = Some(winner.clone());
+
         let max_actions = self
             .detectives
             .iter()
@@ -336,10 +499,11 @@ impl<E: EventListener> Game<E> {
                 .collect(),
             actions,
             winner: winner.clone(),
+            map: None,
         };
 
         self.event_listener.on_game_ended(&replay).await;
-        self.send_game_state(true).await;
+        self.send_game_state(true, false).await;
     }
 
     fn has_connection(&self, from: u8, to: u8, action_type: &dyn ActionTypeTrait) -> bool {
@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use thiserror::Error;
+
+use crate::{
+    data::{Connection, Station, StationType},
+    map::MapFile,
+};
+
+/// a mistake in the data of a map, games on such a map may panic or be unwinnable
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum MapProblem {
+    #[error("station {0} is defined more than once")]
+    DuplicateStation(u8),
+    #[error("connection {from}-{to} references unknown station {missing}")]
+    DanglingConnection { from: u8, to: u8, missing: u8 },
+    #[error("connection {from}-{to} by {mode:?} is defined more than once")]
+    DuplicateConnection { from: u8, to: u8, mode: StationType },
+    #[error("station {station} has a {mode:?} connection but isn't a {mode:?} station")]
+    MissingStationType { station: u8, mode: StationType },
+    #[error("station {station} is a {mode:?} station but has no {mode:?} connection")]
+    UnusedStationType { station: u8, mode: StationType },
+    #[error("station {0} can't be reached from the rest of the map")]
+    UnreachableStation(u8),
+    #[error("{pool} start station {station} does not exist")]
+    UnknownStartStation { pool: &'static str, station: u8 },
+    #[error("{pool} start station {station} can't be reached from the rest of the map")]
+    UnreachableStartStation { pool: &'static str, station: u8 },
+}
+
+impl MapFile {
+    /// checks that stations and connections fit together, an empty list means the map is fine
+    pub fn check(&self) -> Vec<MapProblem> {
+        let mut problems = vec![];
+
+        let mut stations = HashSet::new();
+        for station in &self.stations {
+            if !stations.insert(station.id) {
+                problems.push(MapProblem::DuplicateStation(station.id));
+            }
+        }
+
+        let mut edges = HashSet::new();
+        let mut incident: HashMap<u8, HashSet<&StationType>> = HashMap::new();
+        for Connection { from, to, mode } in &self.connections {
+            let (from, to) = (*from, *to);
+
+            if let Some(missing) = [from, to].into_iter().find(|id| !stations.contains(id)) {
+                problems.push(MapProblem::DanglingConnection { from, to, missing });
+                continue;
+            }
+            if !edges.insert((from.min(to), from.max(to), mode)) {
+                problems.push(MapProblem::DuplicateConnection {
+                    from,
+                    to,
+                    mode: mode.clone(),
+                });
+            }
+
+            incident.entry(from).or_default().insert(mode);
+            incident.entry(to).or_default().insert(mode);
+        }
+
+        for station in &self.stations {
+            let modes = incident.remove(&station.id).unwrap_or_default();
+
+            // water connections are hidden moves between regular stations
+            for mode in modes.iter().filter(|mode| ***mode != StationType::Water) {
+                if !station.types.contains(mode) {
+                    problems.push(MapProblem::MissingStationType {
+                        station: station.id,
+                        mode: (*mode).clone(),
+                    });
+                }
+            }
+            for mode in &station.types {
+                if !modes.contains(mode) {
+                    problems.push(MapProblem::UnusedStationType {
+                        station: station.id,
+                        mode: mode.clone(),
+                    });
+                }
+            }
+        }
+
+        let reachable = largest_component(&self.stations, &self.connections);
+        let start_stations: HashSet<_> = self
+            .start
+            .detectives
+            .iter()
+            .chain(&self.start.mister_x)
+            .collect();
+
+        for station in &self.stations {
+            if !reachable.contains(&station.id) && !start_stations.contains(&station.id) {
+                problems.push(MapProblem::UnreachableStation(station.id));
+            }
+        }
+
+        for (pool, pool_stations) in [
+            ("detective", &self.start.detectives),
+            ("mister x", &self.start.mister_x),
+        ] {
+            for station in pool_stations.iter().copied() {
+                if !stations.contains(&station) {
+                    problems.push(MapProblem::UnknownStartStation { pool, station });
+                } else if !reachable.contains(&station) {
+                    problems.push(MapProblem::UnreachableStartStation { pool, station });
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// the biggest group of stations connected by any means of transport, the other stations
+/// can't be reached from the rest of the map
+fn largest_component(stations: &[Station], connections: &[Connection]) -> HashSet<u8> {
+    let mut neighbours: HashMap<u8, Vec<u8>> = HashMap::new();
+    for connection in connections {
+        neighbours
+            .entry(connection.from)
+            .or_default()
+            .push(connection.to);
+        neighbours
+            .entry(connection.to)
+            .or_default()
+            .push(connection.from);
+    }
+
+    let mut largest = HashSet::new();
+    let mut visited: HashSet<u8> = HashSet::new();
+    for station in stations {
+        if visited.contains(&station.id) {
+            continue;
+        }
+
+        let component = reachable_stations(&neighbours, station.id);
+        visited.extend(&component);
+        if component.len() > largest.len() {
+            largest = component;
+        }
+    }
+
+    largest
+}
+
+/// every station connected to `start`
+fn reachable_stations(neighbours: &HashMap<u8, Vec<u8>>, start: u8) -> HashSet<u8> {
+    let mut reachable = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(station) = queue.pop_front() {
+        for next in neighbours.get(&station).into_iter().flatten() {
+            if reachable.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+
+    reachable
+}
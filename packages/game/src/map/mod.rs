@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+#[cfg(feature = "map-file")]
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::data::{Connection, Round, Station, StationType};
+
+pub use check::MapProblem;
+
+mod check;
+pub mod render;
+
+/// version written by this crate, maps with another version are rejected
+pub const MAP_VERSION: u32 = 1;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MapError {
+    #[error("line {line}, column {column}: {message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("missing field `version`")]
+    MissingVersion,
+    #[error("unsupported map version {0}, expected {MAP_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("{field}: {message}")]
+    InvalidField { field: String, message: String },
+    #[error("line {line} of {file}: {message}")]
+    InvalidLine {
+        file: &'static str,
+        line: usize,
+        message: String,
+    },
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    Problems(Vec<MapProblem>),
+}
+
+impl MapError {
+    fn field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidField {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// a complete map, stored as TOML with one station or connection per line
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct MapFile {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub rules: Rules,
+    pub start: StartPools,
+    pub stations: Vec<Station>,
+    pub connections: Vec<Connection>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub rounds: u8,
+    /// rounds after which Mister X is shown, starting at 1
+    pub reveal_rounds: Vec<u8>,
+    /// one color per detective, limits the number of detectives
+    pub colors: Vec<String>,
+}
+
+/// the rules of the original board game
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            rounds: 24,
+            reveal_rounds: vec![3, 8, 13, 18, 24],
+            colors: ["red", "blue", "green", "yellow", "purple"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+impl Rules {
+    pub fn validate(&self) -> Result<(), MapError> {
+        if self.rounds == 0 {
+            return Err(MapError::field(
+                "rules.rounds",
+                "there has to be at least one round",
+            ));
+        }
+        if let Some(index) = self
+            .reveal_rounds
+            .iter()
+            .position(|round| !(1..=self.rounds).contains(round))
+        {
+            return Err(MapError::field(
+                format!("rules.reveal_rounds[{index}]"),
+                format!("round {} does not exist", self.reveal_rounds[index]),
+            ));
+        }
+
+        let unique_colors: HashSet<_> = self.colors.iter().collect();
+        if unique_colors.is_empty() || unique_colors.len() != self.colors.len() {
+            return Err(MapError::field(
+                "rules.colors",
+                "colors have to be unique and there has to be at least one",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn rounds(&self) -> Vec<Round> {
+        (1..=self.rounds)
+            .map(|index| Round {
+                index,
+                show_mister_x: self.reveal_rounds.contains(&index),
+            })
+            .collect()
+    }
+}
+
+/// stations players can start from, one is picked randomly per player
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StartPools {
+    pub detectives: Vec<u8>,
+    pub mister_x: Vec<u8>,
+}
+
+#[cfg(feature = "map-file")]
+#[derive(Deserialize)]
+struct Versioned {
+    version: Option<u32>,
+}
+
+impl MapFile {
+    #[cfg(feature = "map-file")]
+    /// parses and checks a map, every problem found by [`MapFile::check`] is an error
+    pub fn parse(content: &str) -> Result<Self, MapError> {
+        let map = Self::parse_unchecked(content)?;
+
+        let problems = map.check();
+        if !problems.is_empty() {
+            return Err(MapError::Problems(problems));
+        }
+
+        Ok(map)
+    }
+
+    #[cfg(feature = "map-file")]
+    /// parses a map without checking that its stations and connections fit together
+    pub fn parse_unchecked(content: &str) -> Result<Self, MapError> {
+        let versioned: Versioned =
+            toml::from_str(content).map_err(|err| syntax_error(content, &err))?;
+        match versioned.version {
+            None => return Err(MapError::MissingVersion),
+            Some(MAP_VERSION) => {}
+            Some(version) => return Err(MapError::UnsupportedVersion(version)),
+        }
+
+        let map: MapFile = toml::from_str(content).map_err(|err| syntax_error(content, &err))?;
+        map.rules.validate()?;
+
+        if map.start.detectives.is_empty() {
+            return Err(MapError::field("start.detectives", "must not be empty"));
+        }
+        if map.start.mister_x.is_empty() {
+            return Err(MapError::field("start.mister_x", "must not be empty"));
+        }
+
+        Ok(map)
+    }
+
+    /// converts the old format of `stations.txt` and `connections.txt`,
+    /// which doesn't contain rules or start pools
+    pub fn from_txt(
+        stations: &str,
+        connections: &str,
+        rules: Rules,
+        start: StartPools,
+    ) -> Result<Self, MapError> {
+        Ok(Self {
+            version: MAP_VERSION,
+            name: None,
+            rules,
+            start,
+            stations: parse_txt_stations(stations)?,
+            connections: parse_txt_connections(connections)?,
+        })
+    }
+
+    #[cfg(feature = "map-file")]
+    /// writes one station or connection per line, so errors point to a useful line
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "version = {}", self.version).unwrap();
+        if let Some(name) = &self.name {
+            writeln!(out, "name = {}", inline(name)).unwrap();
+        }
+
+        writeln!(out, "\nstations = [").unwrap();
+        for station in &self.stations {
+            writeln!(out, "    {},", inline(station)).unwrap();
+        }
+        writeln!(out, "]").unwrap();
+
+        writeln!(out, "\nconnections = [").unwrap();
+        for connection in &self.connections {
+            writeln!(out, "    {},", inline(connection)).unwrap();
+        }
+        writeln!(out, "]").unwrap();
+
+        // tables come last, keys after them would belong to the table
+        writeln!(out, "\n[rules]").unwrap();
+        writeln!(out, "rounds = {}", self.rules.rounds).unwrap();
+        writeln!(out, "reveal_rounds = {}", inline(&self.rules.reveal_rounds)).unwrap();
+        writeln!(out, "colors = {}", inline(&self.rules.colors)).unwrap();
+
+        writeln!(out, "\n[start]").unwrap();
+        writeln!(out, "detectives = {}", inline(&self.start.detectives)).unwrap();
+        writeln!(out, "mister_x = {}", inline(&self.start.mister_x)).unwrap();
+
+        out
+    }
+}
+
+#[cfg(feature = "map-file")]
+fn inline<T: Serialize>(value: &T) -> String {
+    toml::Value::try_from(value).unwrap().to_string()
+}
+
+#[cfg(feature = "map-file")]
+fn syntax_error(content: &str, err: &toml::de::Error) -> MapError {
+    let offset = err.span().map(|span| span.start).unwrap_or(0);
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+
+    MapError::Syntax {
+        line,
+        column,
+        message: err.message().to_string(),
+    }
+}
+
+/// one station per line: `id x y type,type,...`
+fn parse_txt_stations(string: &str) -> Result<Vec<Station>, MapError> {
+    string
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let invalid = |message: &str| MapError::InvalidLine {
+                file: "stations.txt",
+                line: index + 1,
+                message: message.to_string(),
+            };
+
+            let parts: Vec<_> = line.split(' ').collect();
+            let [id, pos_x, pos_y, types] = parts[..] else {
+                return Err(invalid("expected `id x y types`"));
+            };
+
+            Ok(Station {
+                id: id.parse().map_err(|_| invalid("invalid id"))?,
+                pos_x: pos_x.parse().map_err(|_| invalid("invalid x position"))?,
+                pos_y: pos_y.parse().map_err(|_| invalid("invalid y position"))?,
+                types: types
+                    .split(',')
+                    .map(|t| match t {
+                        "taxi" => Ok(StationType::Taxi),
+                        "bus" => Ok(StationType::Bus),
+                        "underground" => Ok(StationType::Underground),
+                        _ => Err(invalid(&format!("unknown station type `{t}`"))),
+                    })
+                    .collect::<Result<_, _>>()?,
+                name: None,
+            })
+        })
+        .collect()
+}
+
+/// one connection per line: `from to type`
+fn parse_txt_connections(string: &str) -> Result<Vec<Connection>, MapError> {
+    string
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let invalid = |message: &str| MapError::InvalidLine {
+                file: "connections.txt",
+                line: index + 1,
+                message: message.to_string(),
+            };
+
+            let parts: Vec<_> = line.split(' ').collect();
+            let [from, to, mode] = parts[..] else {
+                return Err(invalid("expected `from to type`"));
+            };
+
+            Ok(Connection {
+                from: from.parse().map_err(|_| invalid("invalid station"))?,
+                to: to.parse().map_err(|_| invalid("invalid station"))?,
+                mode: match mode {
+                    "taxi" => StationType::Taxi,
+                    "bus" => StationType::Bus,
+                    "underground" => StationType::Underground,
+                    "water" => StationType::Water,
+                    _ => return Err(invalid(&format!("unknown connection type `{mode}`"))),
+                },
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,237 @@
+use std::fmt::Write;
+
+use crate::{
+    data::{Connection, Station, StationType},
+    event::GameState,
+    replay::{Action, Replay},
+};
+
+/// space around the outermost stations so their circles aren't cut off
+const PADDING: u32 = 20;
+
+/// positions of the players drawn on top of a rendered map
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overlay {
+    pub detectives: Vec<(String, u8)>,
+    pub mister_x: Option<u8>,
+}
+
+impl Overlay {
+    /// positions after the first `steps` actions of the replay, or at its end
+    pub fn from_replay(replay: &Replay, steps: Option<usize>) -> Self {
+        let mut detectives: Vec<(String, u8)> = replay
+            .detective_starting_stations
+            .iter()
+            .map(|(color, station)| (color.clone(), *station))
+            .collect();
+        detectives.sort();
+        let mut mister_x = replay.mister_x_starting_station;
+
+        for action in replay.actions.iter().take(steps.unwrap_or(usize::MAX)) {
+            match action {
+                Action::Detective { color, station, .. } => {
+                    if let Some(detective) = detectives.iter_mut().find(|(c, _)| c == color) {
+                        detective.1 = *station;
+                    }
+                }
+                Action::MisterX { station, .. } => mister_x = *station,
+            }
+        }
+
+        Self {
+            detectives,
+            mister_x: Some(mister_x),
+        }
+    }
+
+    /// positions of a running game, Mister X is only drawn if the state contains him
+    pub fn from_state(state: &GameState) -> Self {
+        Self {
+            detectives: state
+                .players
+                .iter()
+                .map(|player| (player.color.clone(), player.station_id))
+                .collect(),
+            mister_x: state.mister_x.station_id,
+        }
+    }
+}
+
+/// size and color of a station type, the same as in the client
+pub fn station_type_style(station_type: &StationType) -> (u32, &'static str) {
+    match station_type {
+        StationType::Taxi => (8, "#F2C94C"),
+        StationType::Bus => (12, "#27AE60"),
+        StationType::Underground => (16, "#2F80ED"),
+        StationType::Water => (20, "#0000FF"),
+    }
+}
+
+/// draws the map like the client does, stations are stacked circles and connections lines
+pub fn to_svg(stations: &[Station], connections: &[Connection], overlay: &Overlay) -> String {
+    let min_x = stations.iter().map(|s| s.pos_x).min().unwrap_or(0);
+    let min_y = stations.iter().map(|s| s.pos_y).min().unwrap_or(0);
+    let max_x = stations.iter().map(|s| s.pos_x).max().unwrap_or(0);
+    let max_y = stations.iter().map(|s| s.pos_y).max().unwrap_or(0);
+    let x = min_x as i64 - PADDING as i64;
+    let y = min_y as i64 - PADDING as i64;
+    let width = max_x - min_x + 2 * PADDING;
+    let height = max_y - min_y + 2 * PADDING;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{x} {y} {width} {height}" width="{width}" height="{height}">"#
+    );
+
+    svg.push_str("<g class=\"connections\">\n");
+    let mut connections: Vec<&Connection> = connections.iter().collect();
+    connections.sort_by_key(|connection| std::cmp::Reverse(station_type_style(&connection.mode).0));
+    for connection in connections {
+        let (Some(from), Some(to)) = (
+            find_station(stations, connection.from),
+            find_station(stations, connection.to),
+        ) else {
+            continue;
+        };
+        let (size, color) = station_type_style(&connection.mode);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{color}" stroke-width="{}"/>"#,
+            from.pos_x,
+            from.pos_y,
+            to.pos_x,
+            to.pos_y,
+            size as f32 / 4.0
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str("<g class=\"stations\">\n");
+    for station in stations {
+        let _ = writeln!(
+            svg,
+            "<g><title>{}</title>",
+            escape_xml(&station_title(station))
+        );
+
+        let mut types: Vec<(u32, &str)> = station.types.iter().map(station_type_style).collect();
+        types.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+        for (size, color) in types {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{color}"/>"#,
+                station.pos_x,
+                station.pos_y,
+                size as f32 / 2.0
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-size="4" text-anchor="middle" dominant-baseline="central">{}</text></g>"#,
+            station.pos_x, station.pos_y, station.id
+        );
+    }
+    svg.push_str("</g>\n");
+
+    svg.push_str("<g class=\"overlay\">\n");
+    for (color, station) in &overlay.detectives {
+        if let Some(station) = find_station(stations, *station) {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="12" fill="none" stroke="{}" stroke-width="3"><title>{}</title></circle>"#,
+                station.pos_x,
+                station.pos_y,
+                escape_xml(color),
+                escape_xml(color)
+            );
+        }
+    }
+    if let Some(station) = overlay.mister_x.and_then(|id| find_station(stations, id)) {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="16" fill="none" stroke="black" stroke-width="3" stroke-dasharray="4 2"><title>Mister X</title></circle>"#,
+            station.pos_x, station.pos_y
+        );
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    svg
+}
+
+/// graph of the map for Graphviz, `neato -n` keeps the positions of the stations
+pub fn to_dot(stations: &[Station], connections: &[Connection], overlay: &Overlay) -> String {
+    let mut dot = String::from("graph map {\n    node [shape=circle, style=filled];\n");
+
+    for station in stations {
+        let (_, color) = station
+            .types
+            .iter()
+            .map(station_type_style)
+            .max_by_key(|(size, _)| *size)
+            .unwrap_or((0, "white"));
+
+        let mut attributes = vec![
+            format!("pos=\"{},{}\"", station.pos_x, station.pos_y),
+            format!("fillcolor=\"{color}\""),
+            format!("tooltip=\"{}\"", escape_dot(&station_title(station))),
+        ];
+
+        let mut labels: Vec<String> = overlay
+            .detectives
+            .iter()
+            .filter(|(_, id)| *id == station.id)
+            .map(|(color, _)| color.clone())
+            .collect();
+        if overlay.mister_x == Some(station.id) {
+            labels.push("Mister X".to_string());
+            attributes.push("peripheries=2".to_string());
+        }
+        if let Some((detective, _)) = overlay.detectives.iter().find(|(_, id)| *id == station.id) {
+            attributes.push(format!("color=\"{}\", penwidth=3", escape_dot(detective)));
+        }
+        if !labels.is_empty() {
+            attributes.push(format!("xlabel=\"{}\"", escape_dot(&labels.join(", "))));
+        }
+
+        let _ = writeln!(dot, "    {} [{}];", station.id, attributes.join(", "));
+    }
+
+    for connection in connections {
+        let (size, color) = station_type_style(&connection.mode);
+        let _ = writeln!(
+            dot,
+            "    {} -- {} [color=\"{color}\", penwidth={}];",
+            connection.from,
+            connection.to,
+            size as f32 / 4.0
+        );
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+fn find_station(stations: &[Station], id: u8) -> Option<&Station> {
+    stations.iter().find(|station| station.id == id)
+}
+
+fn station_title(station: &Station) -> String {
+    match &station.name {
+        Some(name) => format!("{} {name}", station.id),
+        None => station.id.to_string(),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
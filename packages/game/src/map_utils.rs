@@ -1,3 +1,7 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
     data::{Connection, StationType},
     event::{DetectiveActionType, DetectiveTransportData, MisterXAbilityData, MisterXActionType},
@@ -35,6 +39,39 @@ pub fn all_valid_mister_x_moves(
         .collect()
 }
 
+/// every single move of Mister X as sent in `LegalMoves`, hidden moves are possible on every connection
+pub fn legal_mister_x_moves(
+    connections: &[Connection],
+    station: u8,
+    ability: &MisterXAbilityData,
+) -> Vec<(u8, MisterXActionType)> {
+    let mut moves = vec![];
+
+    for c in connections
+        .iter()
+        .filter(|c| c.from == station || c.to == station)
+    {
+        let target = if c.from == station { c.to } else { c.from };
+
+        let action_type = match c.mode {
+            StationType::Taxi => Some(MisterXActionType::Taxi),
+            StationType::Bus => Some(MisterXActionType::Bus),
+            StationType::Underground => Some(MisterXActionType::Underground),
+            StationType::Water => None,
+        };
+        if let Some(action_type) = action_type {
+            moves.push((target, action_type));
+        }
+
+        let hidden = (target, MisterXActionType::Hidden);
+        if ability.hidden > 0 && !moves.contains(&hidden) {
+            moves.push(hidden);
+        }
+    }
+
+    moves
+}
+
 pub fn all_valid_detective_moves(
     connections: &[Connection],
     station: u8,
@@ -65,3 +102,189 @@ pub fn all_valid_detective_moves(
         })
         .collect()
 }
+
+/// a station a detective can get to and the fewest moves needed for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReachableStation {
+    pub station: u8,
+    pub moves: usize,
+}
+
+/// one move of a route, `station` is where the move ends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteStep {
+    pub station: u8,
+    pub ticket: DetectiveActionType,
+}
+
+/// every station a detective can get to with the tickets, sorted by the number of moves
+pub fn reachable_stations(
+    connections: &[Connection],
+    from: u8,
+    tickets: &DetectiveTransportData,
+) -> Vec<ReachableStation> {
+    let search = search(connections, from, tickets, None);
+
+    let mut fewest_moves: HashMap<u8, usize> = HashMap::new();
+    for (state, visit) in &search.visits {
+        let moves = fewest_moves.entry(state.station).or_insert(visit.moves);
+        *moves = (*moves).min(visit.moves);
+    }
+    fewest_moves.remove(&from);
+
+    let mut reachable: Vec<_> = fewest_moves
+        .into_iter()
+        .map(|(station, moves)| ReachableStation { station, moves })
+        .collect();
+    reachable.sort_by_key(|reachable| (reachable.moves, reachable.station));
+
+    reachable
+}
+
+/// up to `limit` routes with the fewest moves, empty if `to` can't be reached with the tickets
+pub fn shortest_routes(
+    connections: &[Connection],
+    from: u8,
+    to: u8,
+    tickets: &DetectiveTransportData,
+    limit: usize,
+) -> Vec<Vec<RouteStep>> {
+    let search = search(connections, from, tickets, Some(to));
+
+    let mut routes = vec![];
+    for goal in search.frontier.iter().filter(|state| state.station == to) {
+        collect_routes(&search, *goal, &mut vec![], &mut routes, limit);
+    }
+
+    routes
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct SearchState {
+    station: u8,
+    tickets: DetectiveTransportData,
+}
+
+struct Visit {
+    moves: usize,
+    /// states one move earlier this state was reached from
+    previous: Vec<(SearchState, DetectiveActionType)>,
+}
+
+struct Search {
+    visits: HashMap<SearchState, Visit>,
+    /// states reached with the last move
+    frontier: Vec<SearchState>,
+}
+
+/// breadth first search over stations and remaining tickets, stops at the first move reaching `to`
+///
+/// a state is skipped if the station was already reached with at least the same tickets,
+/// it can't be part of a shortest route and can't reach anything new
+fn search(
+    connections: &[Connection],
+    from: u8,
+    tickets: &DetectiveTransportData,
+    to: Option<u8>,
+) -> Search {
+    let start = SearchState {
+        station: from,
+        tickets: *tickets,
+    };
+
+    let mut visits = HashMap::from([(
+        start,
+        Visit {
+            moves: 0,
+            previous: vec![],
+        },
+    )]);
+    let mut best_tickets: HashMap<u8, Vec<DetectiveTransportData>> =
+        HashMap::from([(from, vec![*tickets])]);
+    let mut frontier = vec![start];
+    let mut moves = 0;
+
+    while !frontier.iter().any(|state| Some(state.station) == to) {
+        moves += 1;
+        let mut next = vec![];
+
+        for state in &frontier {
+            for (station, ticket) in
+                all_valid_detective_moves(connections, state.station, &state.tickets)
+            {
+                let mut tickets = state.tickets;
+                match ticket {
+                    DetectiveActionType::Taxi => tickets.taxi -= 1,
+                    DetectiveActionType::Bus => tickets.bus -= 1,
+                    DetectiveActionType::Underground => tickets.underground -= 1,
+                }
+                let reached = SearchState { station, tickets };
+
+                if let Some(visit) = visits.get_mut(&reached) {
+                    if visit.moves == moves {
+                        visit.previous.push((*state, ticket));
+                    }
+                    continue;
+                }
+
+                let best = best_tickets.entry(station).or_default();
+                if best.iter().any(|best| {
+                    best.taxi >= tickets.taxi
+                        && best.bus >= tickets.bus
+                        && best.underground >= tickets.underground
+                }) {
+                    continue;
+                }
+                best.push(tickets);
+
+                visits.insert(
+                    reached,
+                    Visit {
+                        moves,
+                        previous: vec![(*state, ticket)],
+                    },
+                );
+                next.push(reached);
+            }
+        }
+
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    if to.is_some_and(|to| !frontier.iter().any(|state| state.station == to)) {
+        frontier.clear();
+    }
+
+    Search { visits, frontier }
+}
+
+/// walks back from `state` to the start, `suffix` holds the moves after `state` in reverse
+fn collect_routes(
+    search: &Search,
+    state: SearchState,
+    suffix: &mut Vec<RouteStep>,
+    routes: &mut Vec<Vec<RouteStep>>,
+    limit: usize,
+) {
+    if routes.len() >= limit {
+        return;
+    }
+
+    let previous = &search.visits[&state].previous;
+    if previous.is_empty() {
+        routes.push(suffix.iter().rev().cloned().collect());
+        return;
+    }
+
+    for (previous_state, ticket) in previous {
+        suffix.push(RouteStep {
+            station: state.station,
+            ticket: ticket.clone(),
+        });
+        collect_routes(search, *previous_state, suffix, routes, limit);
+        suffix.pop();
+    }
+}
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "typescript")]
+use ts_rs::TS;
 
 use crate::event::{DetectiveActionType, MisterXActionType, Role};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(TS), ts(rename = "ReplayAction"))]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Action {
     Detective {
@@ -18,10 +21,15 @@ pub enum Action {
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct Replay {
     pub mister_x_starting_station: u8,
+    #[cfg_attr(feature = "typescript", ts(type = "Record<string, number>"))]
     pub detective_starting_stations: HashMap<String, u8>,
     pub actions: Vec<Action>,
     pub winner: Role,
+    /// set by the server, the game itself doesn't know which map it is played on
+    #[serde(default)]
+    pub map: Option<String>,
 }
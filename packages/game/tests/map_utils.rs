@@ -0,0 +1,70 @@
+use game::{
+    data::{Connection, StationType},
+    event::{MisterXAbilityData, MisterXActionType},
+    map_utils::{all_valid_mister_x_moves, legal_mister_x_moves},
+};
+
+fn connections() -> Vec<Connection> {
+    vec![
+        Connection {
+            from: 1,
+            to: 2,
+            mode: StationType::Taxi,
+        },
+        Connection {
+            from: 3,
+            to: 1,
+            mode: StationType::Water,
+        },
+        Connection {
+            from: 1,
+            to: 2,
+            mode: StationType::Bus,
+        },
+        Connection {
+            from: 2,
+            to: 3,
+            mode: StationType::Underground,
+        },
+    ]
+}
+
+fn abilities(hidden: u8) -> MisterXAbilityData {
+    MisterXAbilityData {
+        double_move: 2,
+        hidden,
+    }
+}
+
+#[test]
+fn legal_moves_contain_one_hidden_move_per_station() {
+    assert_eq!(
+        legal_mister_x_moves(&connections(), 1, &abilities(5)),
+        vec![
+            (2, MisterXActionType::Taxi),
+            (2, MisterXActionType::Hidden),
+            (3, MisterXActionType::Hidden),
+            (2, MisterXActionType::Bus),
+        ]
+    );
+}
+
+#[test]
+fn legal_moves_without_hidden_tickets() {
+    assert_eq!(
+        legal_mister_x_moves(&connections(), 1, &abilities(0)),
+        vec![(2, MisterXActionType::Taxi), (2, MisterXActionType::Bus)]
+    );
+}
+
+#[test]
+fn valid_moves_only_use_water_for_hidden_moves() {
+    assert_eq!(
+        all_valid_mister_x_moves(&connections(), 1, &abilities(5)),
+        vec![
+            (2, MisterXActionType::Taxi),
+            (3, MisterXActionType::Hidden),
+            (2, MisterXActionType::Bus),
+        ]
+    );
+}
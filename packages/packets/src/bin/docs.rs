@@ -0,0 +1,14 @@
+//! writes the packet reference and the JSON Schema of every packet:
+//! `cargo run -p packets --bin docs [dir]`, `docs` by default
+
+use std::{fs, path::PathBuf};
+
+fn main() {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or("docs".to_string()));
+
+    for (path, content) in packets::docs::files() {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+    }
+}
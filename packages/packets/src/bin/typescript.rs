@@ -0,0 +1,6 @@
+//! prints the TypeScript definitions of all packets for the web client:
+//! `cargo run -p packets --bin typescript > web/utils/type/packets.ts`
+
+fn main() {
+    print!("{}", packets::typescript::bindings());
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use schemars::Schema;
+use serde_json::Value;
+
+use crate::{ClientPacket, ServerPacket};
+
+const HEADER: &str = "# Packet Reference
+
+Generated from the packets by `cargo run -p packets --bin docs`, do not edit.\\
+The wire format, the handshake and the flow of a game are described in [ws.md](../ws.md).\\
+Every packet has a JSON Schema of its content in [schema](schema), packets without content have `null`.
+";
+
+/// JSON Schema of the content of a packet
+pub struct PacketSchema {
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+impl PacketSchema {
+    /// the schema is titled with the packet, its doc comment becomes the description
+    pub fn new(name: &'static str, docs: &str, mut schema: Schema) -> Self {
+        schema.insert("title".to_string(), name.into());
+        if !docs.is_empty() {
+            schema.insert("description".to_string(), docs.into());
+        }
+
+        Self { name, schema }
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.schema.get("description").and_then(Value::as_str)
+    }
+}
+
+/// paths relative to the docs directory and their content
+pub fn files() -> Vec<(String, String)> {
+    let server = ServerPacket::json_schemas();
+    let client = ClientPacket::json_schemas();
+
+    let mut files = vec![("protocol.md".to_string(), reference(&server, &client))];
+    for (side, schemas) in [("server", &server), ("client", &client)] {
+        for packet in schemas {
+            let json = serde_json::to_string_pretty(&packet.schema).unwrap();
+            files.push((format!("schema/{side}/{}.json", packet.name), json + "\n"));
+        }
+    }
+    files
+}
+
+fn reference(server: &[PacketSchema], client: &[PacketSchema]) -> String {
+    let mut out = HEADER.to_string();
+    let mut types = BTreeMap::new();
+
+    for (title, side, schemas) in [
+        ("Server → Client", "server", server),
+        ("Client → Server", "client", client),
+    ] {
+        out.push_str(&format!("\n## {title}\n"));
+
+        for packet in schemas {
+            out.push_str(&format!("\n### {}\n\n", packet.name));
+            if let Some(description) = packet.description() {
+                out.push_str(&format!("{}\n\n", single_line(description)));
+            }
+            out.push_str(&format!(
+                "`[{}]`, [schema](schema/{side}/{}.json)\n",
+                packet.name, packet.name
+            ));
+            out.push_str(&describe(packet.schema.as_value()));
+
+            if let Some(Value::Object(defs)) = packet.schema.get("$defs") {
+                types.extend(defs.iter().map(|(name, def)| (name.clone(), def.clone())));
+            }
+        }
+    }
+
+    out.push_str("\n## Types\n");
+    for (name, def) in &types {
+        out.push_str(&format!("\n### {name}\n"));
+        if let Some(description) = def.get("description").and_then(Value::as_str) {
+            out.push_str(&format!("\n{}\n", single_line(description)));
+        }
+        out.push_str(&describe(def));
+    }
+
+    out
+}
+
+/// a table for objects, the variants for enums and the type for everything else
+fn describe(schema: &Value) -> String {
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        let required: Vec<_> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut out =
+            "\n| field | type | required | description |\n|---|---|---|---|\n".to_string();
+        for (name, property) in properties {
+            out.push_str(&format!(
+                "| {name} | `{}` | {} | {} |\n",
+                type_name(property).replace('|', "\\|"),
+                if required.contains(&name.as_str()) {
+                    "yes"
+                } else {
+                    "no"
+                },
+                property
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(single_line)
+                    .unwrap_or_default()
+                    .replace('|', "\\|"),
+            ));
+        }
+        return out;
+    }
+
+    if let Some(Value::Array(variants)) = schema.get("oneOf").or_else(|| schema.get("anyOf")) {
+        let mut out = "\none of\n".to_string();
+        for variant in variants {
+            let description = variant
+                .get("description")
+                .and_then(Value::as_str)
+                .map(|description| format!(": {}", single_line(description)))
+                .unwrap_or_default();
+            out.push_str(&format!("- `{}`{description}\n", type_name(variant)));
+        }
+        return out;
+    }
+
+    if schema.get("type").and_then(Value::as_str) == Some("null") {
+        return "\nno content\n".to_string();
+    }
+
+    format!("\ncontent `{}`\n", type_name(schema))
+}
+
+/// short TypeScript-like notation of a schema
+fn type_name(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return join(values.iter().map(Value::to_string));
+    }
+    if let Some(Value::Array(variants)) = schema.get("oneOf").or_else(|| schema.get("anyOf")) {
+        return join(variants.iter().map(type_name));
+    }
+    if let Some(Value::Object(properties)) = schema.get("properties") {
+        let fields: Vec<_> = properties
+            .iter()
+            .map(|(name, property)| format!("{name}: {}", type_name(property)))
+            .collect();
+        return format!("{{ {} }}", fields.join(", "));
+    }
+
+    match schema.get("type") {
+        Some(Value::String(kind)) if kind == "array" => match schema.get("items") {
+            Some(items) => format!("{}[]", type_name(items)),
+            None => "array".to_string(),
+        },
+        Some(Value::String(kind)) => format_or(schema, kind),
+        Some(Value::Array(kinds)) => {
+            join(
+                kinds
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|kind| match kind {
+                        "array" => format!(
+                            "{}[]",
+                            schema.get("items").map(type_name).unwrap_or_default()
+                        ),
+                        "null" => "null".to_string(),
+                        kind => format_or(schema, kind),
+                    }),
+            )
+        }
+        _ => "any".to_string(),
+    }
+}
+
+/// `uuid` or `uint8` say more than `string` or `integer`
+fn format_or(schema: &Value, kind: &str) -> String {
+    schema
+        .get("format")
+        .and_then(Value::as_str)
+        .unwrap_or(kind)
+        .to_string()
+}
+
+fn join(names: impl Iterator<Item = String>) -> String {
+    names.collect::<Vec<_>>().join(" | ")
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,113 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+#[cfg(feature = "typescript")]
+use ts_rs::TS;
+
+use crate::PacketError;
+
+/// how packets are sent on a connection, negotiated with `hello`
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    /// `[name] json` in text frames
+    #[default]
+    Text,
+    /// `[name, content]` in binary frames
+    MessagePack,
+    /// `[name, content]` in binary frames
+    Cbor,
+}
+
+/// content of a packet, kept until the packet and with it the type of the content is known
+pub enum Content {
+    Json(serde_json::Value),
+    /// the encoded content, MessagePack has no value type which keeps enums intact
+    MessagePack(Vec<u8>),
+    Cbor(ciborium::Value),
+}
+
+impl Content {
+    pub fn deserialize<T: DeserializeOwned>(self) -> Result<T, PacketError> {
+        match self {
+            Content::Json(value) => serde_json::from_value(value).ok(),
+            Content::MessagePack(bytes) => rmp_serde::from_slice(&bytes).ok(),
+            Content::Cbor(value) => value.deserialized().ok(),
+        }
+        .ok_or(PacketError::InvalidPacket)
+    }
+}
+
+const NIL: u8 = 0xc0;
+
+/// packets without content have none in text and nil in binary frames
+pub fn encode<T: Serialize>(
+    encoding: Encoding,
+    name: &str,
+    content: Option<&T>,
+) -> Result<Vec<u8>, PacketError> {
+    let serialization = |err: &dyn std::fmt::Display| PacketError::Serialization(err.to_string());
+
+    match encoding {
+        Encoding::Text => match content {
+            Some(content) => serde_json::to_string(content)
+                .map(|content| format!("[{name}] {content}").into_bytes())
+                .map_err(|err| serialization(&err)),
+            None => Ok(format!("[{name}]").into_bytes()),
+        },
+        Encoding::MessagePack => {
+            rmp_serde::to_vec_named(&(name, content)).map_err(|err| serialization(&err))
+        }
+        Encoding::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&(name, content), &mut bytes)
+                .map_err(|err| serialization(&err))?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// name and content of a packet, the name may still contain a request id
+pub fn decode(bytes: &[u8], encoding: Encoding) -> Result<(String, Option<Content>), PacketError> {
+    match encoding {
+        Encoding::Text => {
+            let message = std::str::from_utf8(bytes).map_err(|_| PacketError::InvalidPacket)?;
+            let (name, content) = match message.split_once(' ') {
+                Some((name, content)) => (name, Some(content)),
+                None => (message, None),
+            };
+            let name = name
+                .strip_prefix('[')
+                .and_then(|name| name.strip_suffix(']'))
+                .ok_or(PacketError::UnknownPacket)?;
+            let content = content
+                .map(serde_json::from_str)
+                .transpose()
+                .map_err(|_| PacketError::InvalidPacket)?;
+
+            Ok((name.to_string(), content.map(Content::Json)))
+        }
+        Encoding::MessagePack => {
+            let mut frame = bytes;
+            if rmp::decode::read_array_len(&mut frame).ok() != Some(2) {
+                return Err(PacketError::InvalidPacket);
+            }
+            let (name, content) =
+                rmp::decode::read_str_from_slice(frame).map_err(|_| PacketError::InvalidPacket)?;
+            let content = match content {
+                [] => return Err(PacketError::InvalidPacket),
+                [NIL] => None,
+                content => Some(Content::MessagePack(content.to_vec())),
+            };
+
+            Ok((name.to_string(), content))
+        }
+        Encoding::Cbor => {
+            let (name, content): (String, Option<ciborium::Value>) =
+                ciborium::from_reader(bytes).map_err(|_| PacketError::InvalidPacket)?;
+            Ok((name, content.map(Content::Cbor)))
+        }
+    }
+}
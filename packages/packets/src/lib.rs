@@ -1,48 +1,383 @@
-use std::{error::Error, fmt::Display};
+use std::{borrow::Cow, error::Error};
 
-use game::event::{DetectiveActionType, GameState, MisterXActionType, Role};
+use encoding::Encoding;
+use game::{
+    GameError,
+    event::{DetectiveActionType, GameState, GameStateDelta, LegalMoves, MisterXActionType, Role},
+};
 use packets_derive::Packets;
-use serde::{Deserialize, Serialize, de::DeserializeOwned};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+#[cfg(feature = "typescript")]
+use ts_rs::TS;
 use uuid::Uuid;
 
+#[cfg(feature = "schema")]
+pub mod docs;
+pub mod encoding;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+pub mod version;
+
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct ErrorPacket {
+    pub code: ErrorCode,
     pub message: String,
+    /// more information for some codes, e.g. the reason of an invalid move
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub details: Option<serde_json::Value>,
+    /// name of the client packet which caused the error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub packet: Option<String>,
+    /// request id of the client packet which caused the error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub id: Option<RequestId>,
+}
+
+impl ErrorPacket {
+    pub fn from_error(err: &impl ToErrorCode) -> Self {
+        Self {
+            code: err.code(),
+            message: err.to_string(),
+            details: err.details(),
+            packet: None,
+            id: None,
+        }
+    }
+
+    pub fn with_packet(mut self, packet: Option<String>) -> Self {
+        self.packet = packet;
+        self
+    }
+
+    pub fn with_id(mut self, id: Option<RequestId>) -> Self {
+        self.id = id;
+        self
+    }
+}
+
+/// chosen by the client to match `ack` and `error` packets to the packets it sent
+pub type RequestId = String;
+
+/// sent for every client packet with a request id which was handled without an error
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct AckPacket {
+    pub id: RequestId,
+    pub packet: String,
+}
+
+/// removes the request id from a packet sent as `[name#id] content`
+pub fn split_request_id(message: &str) -> (Cow<'_, str>, Option<RequestId>) {
+    let (name, content) = match message.split_once(' ') {
+        Some((name, content)) => (name, Some(content)),
+        None => (message, None),
+    };
+
+    let Some((name, id)) = name.strip_suffix(']').map(split_name_id) else {
+        return (Cow::Borrowed(message), None);
+    };
+    let Some(id) = id else {
+        return (Cow::Borrowed(message), None);
+    };
+
+    let message = match content {
+        Some(content) => format!("{name}] {content}"),
+        None => format!("{name}]"),
+    };
+
+    (Cow::Owned(message), Some(id))
+}
+
+/// `name#id` in binary frames
+fn split_name_id(name: &str) -> (&str, Option<RequestId>) {
+    match name.split_once('#') {
+        Some((name, id)) => (name, (!id.is_empty()).then(|| id.to_string())),
+        None => (name, None),
+    }
+}
+
+/// a client packet as it was received, name and request id are kept if it can't be parsed
+pub struct ReceivedPacket {
+    pub name: Option<String>,
+    pub id: Option<RequestId>,
+    pub packet: Result<ClientPacket, PacketError>,
+}
+
+impl ReceivedPacket {
+    pub fn from_text(message: &str, protocol_version: u32) -> Self {
+        let (message, id) = split_request_id(message);
+        let message = version::upgrade_client_message(&message, protocol_version);
+
+        Self {
+            name: Some(packet_name(&message).to_string()),
+            id,
+            packet: ClientPacket::from_string(&message),
+        }
+    }
+
+    pub fn from_binary(bytes: &[u8], encoding: Encoding) -> Self {
+        match encoding::decode(bytes, encoding) {
+            Ok((name, content)) => {
+                let (name, id) = split_name_id(&name);
+
+                Self {
+                    name: Some(name.to_string()),
+                    id,
+                    packet: ClientPacket::from_content(name, content),
+                }
+            }
+            Err(err) => Self {
+                name: None,
+                id: None,
+                packet: Err(err),
+            },
+        }
+    }
+}
+
+/// stable identifier of an error, clients should match on it instead of the message
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    UnknownPacket,
+    InvalidPacket,
+    UnsupportedProtocolVersion,
+
+    GameAlreadyJoined,
+    NotInGame,
+    NotInLobby,
+    UnknownBot,
+    NoRematch,
+    UnknownSession,
+
+    UnknownLobby,
+    ReservedForRematch,
+    LobbyFull,
+    InvalidNumberOfDetectives,
+    NotEnoughPlayers,
+    TooManyLobbies,
+    UnknownMap,
+
+    UnknownGame,
+    NotYourTurn,
+    /// the packet is only sent by the other role
+    WrongRole,
+    OmniscientSpectatorsDisabled,
+    TooManySpectators,
+
+    InvalidMove,
+    NotAllMoved,
+
+    /// something went wrong on the server, e.g. storing the replay
+    Internal,
+}
+
+/// errors which are sent to clients
+pub trait ToErrorCode: Error {
+    fn code(&self) -> ErrorCode;
+
+    fn details(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+impl ToErrorCode for GameError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            GameError::InvalidMove(_) => ErrorCode::InvalidMove,
+            GameError::NotAllMoved => ErrorCode::NotAllMoved,
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            GameError::InvalidMove(reason) => Some(serde_json::json!({ "reason": reason })),
+            GameError::NotAllMoved => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct SpectatorSettings {
+    /// whether spectators may watch with Mister X visible
+    pub allow_omniscient: bool,
+    /// number of rounds the omniscient view of Mister X lags behind
+    pub omniscient_delay: u8,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct CreateGamePacket {
     pub number_of_detectives: usize,
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional, as = "Option<SpectatorSettings>"))]
+    pub spectators: SpectatorSettings,
+    /// public lobbies are listed by `GET /game/lobbies`
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional, as = "Option<bool>"))]
+    pub public: bool,
+    /// id of a map listed by `GET /map`, the default map if not set
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub map: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct GamePacket {
     pub id: Uuid,
+    pub code: String,
+}
+
+/// a lobby is either referenced by its id or by its short join code
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(untagged)]
+pub enum LobbyReference {
+    Id(Uuid),
+    Code(String),
+}
+
+/// first packet of a client, connections without it use version 1
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct HelloPacket {
+    pub protocol_version: u32,
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional))]
+    pub client_name: Option<String>,
+    /// the server sends `welcome` and all further packets with it
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional, as = "Option<Encoding>"))]
+    pub encoding: Encoding,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct WelcomePacket {
+    /// version used on this connection
+    pub protocol_version: u32,
+    /// newest version the server speaks
+    pub server_protocol_version: u32,
+    pub encoding: Encoding,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct JoinGamePacket {
+    #[cfg_attr(feature = "typescript", ts(type = "string"))]
+    pub id: LobbyReference,
+    /// sends `LegalMoves` after every `StartMove` of the own side
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional, as = "Option<bool>"))]
+    pub legal_moves: bool,
+    /// sends `GameStateDelta` instead of `GameState` for tentative moves
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(optional, as = "Option<bool>"))]
+    pub state_deltas: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct AddBotPacket {
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+#[serde(rename_all = "snake_case")]
+pub enum SpectatorView {
+    Detective,
+    Omniscient,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct SpectateGamePacket {
     pub id: Uuid,
+    pub view: SpectatorView,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct SpectatingPacket {
+    pub view: SpectatorView,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct LobbyPlayerData {
+    pub id: Uuid,
+    pub score: u32,
+    pub accepted_rematch: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct LobbyStatePacket {
+    pub id: Uuid,
+    pub players: Vec<LobbyPlayerData>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct GameStartedPacket {
     pub role: Role,
+    /// secret to resume the game with after reconnecting
+    pub session: Uuid,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
+pub struct ResumeGamePacket {
+    pub session: Uuid,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct StartMovePacket {
     pub role: Role,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct MoveMisterXPacket {
     pub station_id: u8,
     pub transport_type: MisterXActionType,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct MoveDetectivePacket {
     pub color: String,
     pub station_id: u8,
@@ -50,6 +385,8 @@ pub struct MoveDetectivePacket {
 }
 
 #[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "typescript", derive(TS))]
 pub struct GameEndedPacket {
     pub winner: Role,
 }
@@ -60,33 +397,77 @@ pub enum PacketError {
     UnknownPacket,
     #[error("invalid packet")]
     InvalidPacket,
+    #[error("failed to serialize packet: {0}")]
+    Serialization(String),
+}
+
+/// dependencies of the code generated by the `Packets` derive
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "schema")]
+    pub use schemars;
+    pub use serde;
+    pub use serde_json;
+    #[cfg(feature = "typescript")]
+    pub use ts_rs;
+}
+
+/// name of a packet sent as text, without brackets, even if the packet can't be parsed
+pub fn packet_name(message: &str) -> &str {
+    let name = message.split(' ').next().unwrap_or_default();
+    name.strip_prefix('[')
+        .and_then(|name| name.strip_suffix(']'))
+        .unwrap_or(name)
+}
+
+impl ToErrorCode for PacketError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            PacketError::UnknownPacket => ErrorCode::UnknownPacket,
+            PacketError::InvalidPacket => ErrorCode::InvalidPacket,
+            PacketError::Serialization(_) => ErrorCode::Internal,
+        }
+    }
 }
 
 #[derive(Packets, Clone)]
+#[packet(crate = "crate")]
 pub enum ServerPacket {
     Error(ErrorPacket),
+    Ack(AckPacket),
+    Welcome(WelcomePacket),
     Game(GamePacket),
+    LobbyState(LobbyStatePacket),
     GameStarted(GameStartedPacket),
+    Spectating(SpectatingPacket),
     StartMove(StartMovePacket),
+    LegalMoves(LegalMoves),
     GameState(GameState),
+    GameStateDelta(GameStateDelta),
     EndMove,
     GameEnded(GameEndedPacket),
 }
 
 impl ServerPacket {
-    pub fn from_error(err: impl Error) -> ServerPacket {
-        ServerPacket::Error(ErrorPacket {
-            message: err.to_string(),
-        })
+    pub fn from_error(err: impl ToErrorCode) -> ServerPacket {
+        ServerPacket::Error(ErrorPacket::from_error(&err))
     }
 }
 
 #[derive(Packets, Clone)]
+#[packet(crate = "crate")]
 pub enum ClientPacket {
+    Hello(HelloPacket),
     CreateGame(CreateGamePacket),
     JoinGame(JoinGamePacket),
+    SpectateGame(SpectateGamePacket),
+    ResumeGame(ResumeGamePacket),
+    AddBot(AddBotPacket),
     StartGame,
     MoveMisterX(Vec<MoveMisterXPacket>),
     MoveDetective(MoveDetectivePacket),
     SubmitMove,
+    Rematch,
+    /// asks for the full `GameState` after a gap in the `seq` of the deltas
+    Resync,
 }
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use game::{
+    data::{Connection, Station},
+    replay::Replay,
+};
+use ts_rs::{TS, TypeVisitor};
+
+use crate::{ClientPacket, ServerPacket, version::PROTOCOL_VERSION};
+
+const HEADER: &str = "// generated by `cargo run -p packets --bin typescript`, do not edit\n";
+
+/// the `[name] content` text format, see ws.md
+const WIRE_FORMAT: &str = r#"/** sends `[name] content`, the request id comes back in `ack` and `error` */
+export function encodePacket(packet: ClientPacket, id?: string): string {
+	const name = id === undefined ? packet.name : `${packet.name}#${id}`;
+	return "content" in packet
+		? `[${name}] ${JSON.stringify(packet.content)}`
+		: `[${name}]`;
+}
+
+/** parses a text frame sent by the server */
+export function decodePacket(message: string): ServerPacket {
+	const space = message.indexOf(" ");
+	if (space === -1) {
+		return { name: message.slice(1, -1) } as ServerPacket;
+	}
+	return {
+		name: message.slice(1, space - 1),
+		content: JSON.parse(message.slice(space + 1)),
+	} as ServerPacket;
+}
+"#;
+
+/// declarations of all types used by packets, sorted by name
+#[derive(Default)]
+struct Declarations(BTreeMap<String, String>);
+
+impl TypeVisitor for Declarations {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        // wrappers like `Vec` are visited with their generics instead
+        if T::output_path().is_none() || self.0.contains_key(&T::ident()) {
+            return;
+        }
+
+        let docs = T::docs().unwrap_or_default();
+        self.0
+            .insert(T::ident(), format!("{docs}export {}\n", T::decl()));
+        T::visit_dependencies(self);
+    }
+}
+
+/// TypeScript definitions of all packets, the HTTP API types and the helpers to send and parse packets
+pub fn bindings() -> String {
+    let mut declarations = Declarations::default();
+    ServerPacket::visit_typescript_dependencies(&mut declarations);
+    ClientPacket::visit_typescript_dependencies(&mut declarations);
+    // returned by `/map` and `/game/replays`
+    declarations.visit::<Station>();
+    declarations.visit::<Connection>();
+    declarations.visit::<Replay>();
+
+    let mut out = HEADER.to_string();
+    out.push_str(&format!(
+        "\nexport const PROTOCOL_VERSION = {PROTOCOL_VERSION};\n"
+    ));
+    for declaration in declarations.0.values() {
+        out.push('\n');
+        out.push_str(declaration);
+    }
+    out.push('\n');
+    out.push_str(&ServerPacket::typescript());
+    out.push('\n');
+    out.push_str(&ClientPacket::typescript());
+    out.push('\n');
+    out.push_str(WIRE_FORMAT);
+    out
+}
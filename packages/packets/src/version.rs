@@ -0,0 +1,66 @@
+use std::borrow::Cow;
+
+use thiserror::Error;
+
+use crate::{ErrorCode, ErrorPacket, ToErrorCode, packet_name};
+
+/// version of the protocol described by this crate
+pub const PROTOCOL_VERSION: u32 = 3;
+/// oldest version the server still adapts packets from, also used by clients without `hello`
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum VersionError {
+    #[error(
+        "unsupported protocol version {0}, must be between {MIN_PROTOCOL_VERSION} and {PROTOCOL_VERSION}"
+    )]
+    Unsupported(u32),
+}
+
+impl ToErrorCode for VersionError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::UnsupportedProtocolVersion
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "min": MIN_PROTOCOL_VERSION,
+            "max": PROTOCOL_VERSION,
+        }))
+    }
+}
+
+/// the version used on a connection, clients newer than the server have to fall back to it
+pub fn negotiate(client_version: u32) -> Result<u32, VersionError> {
+    if client_version < MIN_PROTOCOL_VERSION {
+        return Err(VersionError::Unsupported(client_version));
+    }
+
+    Ok(client_version.min(PROTOCOL_VERSION))
+}
+
+/// rewrites a client packet of an older version to the current one
+///
+/// version 1: `moveMisterX` took a single move instead of a list
+pub fn upgrade_client_message(message: &str, version: u32) -> Cow<'_, str> {
+    if version < 2
+        && packet_name(message) == "moveMisterX"
+        && let Some((name, content)) = message.split_once(' ')
+        && content.trim_start().starts_with('{')
+    {
+        return Cow::Owned(format!("{name} [{content}]"));
+    }
+
+    Cow::Borrowed(message)
+}
+
+/// rewrites an error for a connection of an older version
+///
+/// version 2: packets of the other role were rejected with `not_your_turn`
+pub fn downgrade_error(mut error: ErrorPacket, version: u32) -> ErrorPacket {
+    if version < 3 && error.code == ErrorCode::WrongRole {
+        error.code = ErrorCode::NotYourTurn;
+    }
+
+    error
+}
@@ -0,0 +1,184 @@
+use packets::{
+    ClientPacket, ErrorCode, ErrorPacket, PacketError, ServerPacket,
+    version::{
+        MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, VersionError, downgrade_error, negotiate,
+        upgrade_client_message,
+    },
+};
+use serde_json::Value;
+
+fn parse(message: &str, version: u32) -> Result<ClientPacket, PacketError> {
+    ClientPacket::from_string(&upgrade_client_message(message, version))
+}
+
+fn content(packet: &ServerPacket) -> Value {
+    let message = packet.to_text().unwrap();
+    let (_, content) = message.split_once(' ').unwrap();
+    serde_json::from_str(content).unwrap()
+}
+
+#[test]
+fn negotiates_the_common_version() {
+    assert_eq!(negotiate(PROTOCOL_VERSION), Ok(PROTOCOL_VERSION));
+    assert_eq!(negotiate(MIN_PROTOCOL_VERSION), Ok(MIN_PROTOCOL_VERSION));
+    assert_eq!(negotiate(PROTOCOL_VERSION + 1), Ok(PROTOCOL_VERSION));
+    assert_eq!(
+        negotiate(MIN_PROTOCOL_VERSION - 1),
+        Err(VersionError::Unsupported(MIN_PROTOCOL_VERSION - 1))
+    );
+}
+
+#[test]
+fn version_1_mister_x_moves_are_upgraded() {
+    let Ok(ClientPacket::MoveMisterX(moves)) = parse(
+        r#"[moveMisterX] {"station_id": 110, "transport_type": "hidden"}"#,
+        1,
+    ) else {
+        panic!("expected moveMisterX");
+    };
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].station_id, 110);
+
+    let Ok(ClientPacket::MoveMisterX(moves)) = parse(
+        r#"[moveMisterX] [{"station_id": 110, "transport_type": "taxi"}]"#,
+        1,
+    ) else {
+        panic!("expected moveMisterX");
+    };
+    assert_eq!(moves.len(), 1);
+}
+
+#[test]
+fn current_version_is_not_upgraded() {
+    assert_eq!(
+        parse(
+            r#"[moveMisterX] {"station_id": 110, "transport_type": "taxi"}"#,
+            PROTOCOL_VERSION
+        )
+        .err(),
+        Some(PacketError::InvalidPacket)
+    );
+    assert_eq!(
+        upgrade_client_message("[submitMove]", 1),
+        upgrade_client_message("[submitMove]", PROTOCOL_VERSION)
+    );
+}
+
+#[test]
+fn packets_of_every_version_parse() {
+    let messages = [
+        r#"[hello] {"protocol_version": 1}"#,
+        r#"[hello] {"protocol_version": 2, "client_name": "bot"}"#,
+        r#"[createGame] {"number_of_detectives": 4}"#,
+        r#"[joinGame] {"id": "67e55044-10b1-426f-9247-bb680e5fe0c8"}"#,
+        r#"[joinGame] {"id": "ABCD", "legal_moves": true}"#,
+        r#"[moveDetective] {"color": "red", "station_id": 1, "transport_type": "bus"}"#,
+        "[startGame]",
+        "[submitMove]",
+        "[rematch]",
+    ];
+
+    for version in MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION {
+        for message in messages {
+            assert!(
+                parse(message, version).is_ok(),
+                "{message} failed with version {version}"
+            );
+        }
+    }
+}
+
+#[test]
+fn errors_keep_the_message_of_version_1() {
+    let error = content(&ServerPacket::from_error(PacketError::UnknownPacket));
+
+    assert_eq!(error["message"], "unknown packet");
+    assert_eq!(error["code"], "unknown_packet");
+    assert_eq!(
+        serde_json::to_value(ErrorCode::UnsupportedProtocolVersion).unwrap(),
+        "unsupported_protocol_version"
+    );
+
+    let error = ErrorPacket::from_error(&VersionError::Unsupported(0));
+    assert_eq!(error.code, ErrorCode::UnsupportedProtocolVersion);
+    assert_eq!(
+        error.details,
+        Some(serde_json::json!({ "min": MIN_PROTOCOL_VERSION, "max": PROTOCOL_VERSION }))
+    );
+}
+
+#[test]
+fn errors_of_newer_versions_are_downgraded() {
+    let error = ErrorPacket::from_error(&PacketError::UnknownPacket);
+    let wrong_role = ErrorPacket {
+        code: ErrorCode::WrongRole,
+        ..error
+    };
+
+    assert_eq!(
+        downgrade_error(wrong_role.clone(), 2).code,
+        ErrorCode::NotYourTurn
+    );
+    assert_eq!(
+        downgrade_error(wrong_role, PROTOCOL_VERSION).code,
+        ErrorCode::WrongRole
+    );
+}
+
+/// the shape is taken from the JSON Schemas of the packets
+#[cfg(feature = "schema")]
+mod wire_shape {
+    use packets::{ClientPacket, ServerPacket, docs::PacketSchema, version::PROTOCOL_VERSION};
+    use serde_json::Value;
+
+    /// fingerprints of the wire shape of every version since they are tracked,
+    /// a changed shape needs a new version and existing entries are never changed
+    const WIRE_SHAPES: &[(u32, u64)] = &[(3, 0xb268_e8a8_ea34_4852)];
+
+    #[test]
+    fn wire_shape_changes_bump_the_version() {
+        let shape = wire_shape();
+
+        assert!(
+            WIRE_SHAPES.last() == Some(&(PROTOCOL_VERSION, shape)),
+            "the wire shape changed to {shape:#x}, bump PROTOCOL_VERSION and add it to WIRE_SHAPES"
+        );
+        assert!(
+            WIRE_SHAPES.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "WIRE_SHAPES has to be sorted by version"
+        );
+    }
+
+    /// FNV-1a of the schemas of every packet without their descriptions, stable across builds
+    fn wire_shape() -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+        for (side, schemas) in [
+            ("server", ServerPacket::json_schemas()),
+            ("client", ClientPacket::json_schemas()),
+        ] {
+            for PacketSchema { name, schema } in schemas {
+                let mut schema = schema.to_value();
+                strip_descriptions(&mut schema);
+
+                for byte in format!("{side} {name} {schema}\n").bytes() {
+                    hash ^= u64::from(byte);
+                    hash = hash.wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+        }
+
+        hash
+    }
+
+    fn strip_descriptions(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                map.remove("description");
+                map.values_mut().for_each(strip_descriptions);
+            }
+            Value::Array(values) => values.iter_mut().for_each(strip_descriptions),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use packets::{PacketError, encoding::Encoding};
+use packets_derive::Packets;
+
+mod reexported {
+    pub use packets as wire;
+}
+
+#[derive(Packets, Clone, Debug, PartialEq)]
+enum TestPacket {
+    Ping,
+    #[packet(name = "chat.message")]
+    Message(String),
+    Move {
+        color: String,
+        /// where the piece ends up
+        station_id: u8,
+    },
+    Pair(u8, String),
+    /// closes the connection
+    #[packet(name = "bye")]
+    Quit,
+    Scores(BTreeMap<Vec<u8>, u8>),
+    Étape(u8),
+}
+
+/// the derive finds the packets crate under another path
+#[derive(Packets, Clone, Debug, PartialEq)]
+#[packet(crate = "crate::reexported::wire")]
+enum RenamedCratePacket {
+    Ping,
+    Move { station_id: u8 },
+}
+
+fn samples() -> Vec<(TestPacket, &'static str)> {
+    vec![
+        (TestPacket::Ping, "[ping]"),
+        (
+            TestPacket::Message("hi".to_string()),
+            r#"[chat.message] "hi""#,
+        ),
+        (
+            TestPacket::Move {
+                color: "red".to_string(),
+                station_id: 106,
+            },
+            r#"[move] {"color":"red","station_id":106}"#,
+        ),
+        (TestPacket::Pair(1, "a".to_string()), r#"[pair] [1,"a"]"#),
+        (TestPacket::Quit, "[bye]"),
+        (TestPacket::Étape(3), "[étape] 3"),
+    ]
+}
+
+#[test]
+fn variants_are_sent_as_text() {
+    for (packet, text) in samples() {
+        assert_eq!(packet.to_text().unwrap(), text);
+        assert_eq!(TestPacket::from_string(text).unwrap(), packet);
+    }
+}
+
+#[test]
+fn variants_round_trip_in_binary() {
+    for encoding in [Encoding::MessagePack, Encoding::Cbor] {
+        for (packet, _) in samples() {
+            let bytes = packet.to_binary(encoding).unwrap();
+            assert_eq!(TestPacket::from_binary(&bytes, encoding).unwrap(), packet);
+        }
+    }
+}
+
+#[test]
+fn renamed_packets_are_only_known_by_their_name() {
+    assert_eq!(TestPacket::Quit.name(), "bye");
+    assert_eq!(
+        TestPacket::from_string("[quit]"),
+        Err(PacketError::UnknownPacket)
+    );
+    assert_eq!(
+        TestPacket::from_string("[message] \"hi\""),
+        Err(PacketError::UnknownPacket)
+    );
+}
+
+#[test]
+fn missing_fields_are_invalid() {
+    for text in [
+        r#"[move] {"color":"red"}"#,
+        "[move]",
+        r#"[pair] [1]"#,
+        r#"[pair] {"color":"red"}"#,
+    ] {
+        assert_eq!(
+            TestPacket::from_string(text),
+            Err(PacketError::InvalidPacket),
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn serialization_errors_are_returned() {
+    // JSON only has string keys
+    let packet = TestPacket::Scores(BTreeMap::from([(vec![1], 2)]));
+
+    assert!(matches!(
+        packet.to_text(),
+        Err(PacketError::Serialization(_))
+    ));
+    assert!(packet.to_binary(Encoding::MessagePack).is_ok());
+}
+
+#[test]
+#[cfg(all(feature = "typescript", feature = "schema"))]
+fn struct_and_tuple_variants_are_described() {
+    let typescript = TestPacket::typescript();
+    assert!(typescript.contains(r#"{ name: "move"; content: { color: string, "#));
+    assert!(typescript.contains("where the piece ends up"));
+    assert!(typescript.contains("station_id: number } }"));
+    assert!(typescript.contains(r#"{ name: "pair"; content: [number, string] }"#));
+    assert!(typescript.contains(r#"{ name: "bye" }"#));
+
+    let schemas = TestPacket::json_schemas();
+    let schema = |name: &str| {
+        let packet = schemas.iter().find(|packet| packet.name == name).unwrap();
+        packet.schema.as_value().clone()
+    };
+
+    let move_schema = schema("move");
+    assert_eq!(
+        move_schema["required"],
+        serde_json::json!(["color", "station_id"])
+    );
+    assert_eq!(
+        move_schema["properties"]["station_id"]["description"],
+        "where the piece ends up"
+    );
+    assert_eq!(schema("pair")["type"], "array");
+    assert_eq!(schema("bye")["description"], "closes the connection");
+}
+
+#[test]
+fn crate_path_can_be_set() {
+    let packet = RenamedCratePacket::Move { station_id: 106 };
+
+    assert_eq!(packet.to_text().unwrap(), r#"[move] {"station_id":106}"#);
+    assert_eq!(
+        RenamedCratePacket::from_string("[ping]"),
+        Ok(RenamedCratePacket::Ping)
+    );
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "schema")]
+
+use std::{fs, path::Path};
+
+use packets::docs::files;
+
+const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../docs");
+
+#[test]
+fn docs_are_up_to_date() {
+    let dir = Path::new(DIR);
+
+    for (path, content) in files() {
+        let committed = fs::read_to_string(dir.join(&path)).unwrap_or_default();
+        assert!(
+            committed == content,
+            "docs/{path} is outdated, run `cargo run -p packets --bin docs`"
+        );
+    }
+}
+
+#[test]
+fn no_schema_of_a_removed_packet_is_left() {
+    let generated: Vec<_> = files().into_iter().map(|(path, _)| path).collect();
+
+    for side in ["server", "client"] {
+        for entry in fs::read_dir(Path::new(DIR).join("schema").join(side)).unwrap() {
+            let path = format!(
+                "schema/{side}/{}",
+                entry.unwrap().file_name().to_string_lossy()
+            );
+            assert!(generated.contains(&path), "docs/{path} has no packet");
+        }
+    }
+}
+
+#[test]
+fn schemas_describe_the_content() {
+    let files = files();
+    let schema = |path: &str| -> serde_json::Value {
+        let (_, content) = files.iter().find(|(file, _)| file == path).unwrap();
+        serde_json::from_str(content).unwrap()
+    };
+
+    let game_state = schema("schema/server/gameState.json");
+    assert_eq!(game_state["title"], "gameState");
+    assert!(
+        game_state["$defs"]["MisterXAbilityData"]["properties"]
+            .get("double_move")
+            .is_some()
+    );
+
+    let game = schema("schema/server/game.json");
+    assert_eq!(game["required"], serde_json::json!(["id", "code"]));
+
+    let end_move = schema("schema/server/endMove.json");
+    assert_eq!(end_move["type"], "null");
+
+    let resync = schema("schema/client/resync.json");
+    assert_eq!(
+        resync["description"],
+        "asks for the full `GameState` after a gap in the `seq` of the deltas"
+    );
+}
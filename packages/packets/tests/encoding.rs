@@ -0,0 +1,175 @@
+use packets::{
+    ClientPacket, PacketError, ServerPacket,
+    encoding::{Encoding, encode},
+};
+
+const ENCODINGS: [Encoding; 3] = [Encoding::Text, Encoding::MessagePack, Encoding::Cbor];
+
+const ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+fn server_packets() -> Vec<ServerPacket> {
+    [
+        r#"[error] {"code":"invalid_move","message":"invalid move","details":{"reason":"no_connection"},"packet":"moveMisterX","id":"1"}"#.to_string(),
+        r#"[ack] {"id":"1","packet":"submitMove"}"#.to_string(),
+        r#"[welcome] {"protocol_version":2,"server_protocol_version":2,"encoding":"cbor"}"#.to_string(),
+        format!(r#"[game] {{"id":"{ID}","code":"ABCD"}}"#),
+        format!(r#"[lobbyState] {{"id":"{ID}","players":[{{"id":"{ID}","score":2,"accepted_rematch":true}}]}}"#),
+        format!(r#"[gameStarted] {{"role":"mister_x","session":"{ID}"}}"#),
+        r#"[spectating] {"view":"omniscient"}"#.to_string(),
+        r#"[startMove] {"role":"detective"}"#.to_string(),
+        r#"[legalMoves] {"role":"mister_x","moves":[{"station_id":110,"transport_type":"hidden"}]}"#.to_string(),
+        r#"[legalMoves] {"role":"detective","moves":[{"color":"red","station_id":106,"transport_type":"taxi"}]}"#.to_string(),
+        r#"[gameState] {"players":[{"color":"red","station_id":100,"available_transport":{"taxi":10,"bus":8,"underground":4}}],"mister_x":{"station_id":null,"abilities":{"double_move":2,"hidden":5},"moves":["taxi","hidden"]},"round":3,"seq":7}"#.to_string(),
+        r#"[gameStateDelta] {"seq":8,"changes":[{"type":"piece_moved","color":"red","station_id":106},{"type":"tickets_changed","color":"red","available_transport":{"taxi":9,"bus":8,"underground":4}},{"type":"round_advanced","round":4}]}"#.to_string(),
+        "[endMove]".to_string(),
+        r#"[gameEnded] {"winner":"detective"}"#.to_string(),
+    ]
+    .iter()
+    .map(|message| ServerPacket::from_string(message).unwrap())
+    .collect()
+}
+
+fn client_packets() -> Vec<ClientPacket> {
+    [
+        r#"[hello] {"protocol_version":2,"client_name":"bot","encoding":"message_pack"}"#.to_string(),
+        r#"[createGame] {"number_of_detectives":4,"spectators":{"allow_omniscient":true,"omniscient_delay":2},"public":true,"map":"london"}"#.to_string(),
+        format!(r#"[joinGame] {{"id":"{ID}","legal_moves":true}}"#),
+        r#"[joinGame] {"id":"ABCD","legal_moves":false}"#.to_string(),
+        format!(r#"[spectateGame] {{"id":"{ID}","view":"detective"}}"#),
+        format!(r#"[resumeGame] {{"session":"{ID}"}}"#),
+        r#"[addBot] {"name":"random"}"#.to_string(),
+        "[startGame]".to_string(),
+        r#"[moveMisterX] [{"station_id":110,"transport_type":"taxi"},{"station_id":120,"transport_type":"hidden"}]"#.to_string(),
+        r#"[moveDetective] {"color":"red","station_id":106,"transport_type":"underground"}"#.to_string(),
+        "[submitMove]".to_string(),
+        "[rematch]".to_string(),
+        "[resync]".to_string(),
+    ]
+    .iter()
+    .map(|message| ClientPacket::from_string(message).unwrap())
+    .collect()
+}
+
+/// fails to compile when a variant is added, so the samples above stay complete
+fn server_variant(packet: &ServerPacket) -> usize {
+    match packet {
+        ServerPacket::Error(_) => 0,
+        ServerPacket::Ack(_) => 1,
+        ServerPacket::Welcome(_) => 2,
+        ServerPacket::Game(_) => 3,
+        ServerPacket::LobbyState(_) => 4,
+        ServerPacket::GameStarted(_) => 5,
+        ServerPacket::Spectating(_) => 6,
+        ServerPacket::StartMove(_) => 7,
+        ServerPacket::LegalMoves(_) => 8,
+        ServerPacket::GameState(_) => 9,
+        ServerPacket::GameStateDelta(_) => 10,
+        ServerPacket::EndMove => 11,
+        ServerPacket::GameEnded(_) => 12,
+    }
+}
+
+fn client_variant(packet: &ClientPacket) -> usize {
+    match packet {
+        ClientPacket::Hello(_) => 0,
+        ClientPacket::CreateGame(_) => 1,
+        ClientPacket::JoinGame(_) => 2,
+        ClientPacket::SpectateGame(_) => 3,
+        ClientPacket::ResumeGame(_) => 4,
+        ClientPacket::AddBot(_) => 5,
+        ClientPacket::StartGame => 6,
+        ClientPacket::MoveMisterX(_) => 7,
+        ClientPacket::MoveDetective(_) => 8,
+        ClientPacket::SubmitMove => 9,
+        ClientPacket::Rematch => 10,
+        ClientPacket::Resync => 11,
+    }
+}
+
+#[test]
+fn samples_cover_every_variant() {
+    let mut server: Vec<_> = server_packets().iter().map(server_variant).collect();
+    server.dedup();
+    assert_eq!(server, (0..13).collect::<Vec<_>>());
+
+    let mut client: Vec<_> = client_packets().iter().map(client_variant).collect();
+    client.dedup();
+    assert_eq!(client, (0..12).collect::<Vec<_>>());
+}
+
+#[test]
+fn server_packets_round_trip() {
+    for encoding in ENCODINGS {
+        for packet in server_packets() {
+            let decoded = ServerPacket::from_binary(&packet.to_binary(encoding).unwrap(), encoding)
+                .unwrap_or_else(|err| panic!("{} failed with {encoding:?}: {err}", packet.name()));
+            assert_eq!(
+                decoded.to_text().unwrap(),
+                packet.to_text().unwrap(),
+                "{encoding:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn client_packets_round_trip() {
+    for encoding in ENCODINGS {
+        for packet in client_packets() {
+            let decoded = ClientPacket::from_binary(&packet.to_binary(encoding).unwrap(), encoding)
+                .unwrap_or_else(|err| panic!("{} failed with {encoding:?}: {err}", packet.name()));
+            assert_eq!(
+                decoded.to_text().unwrap(),
+                packet.to_text().unwrap(),
+                "{encoding:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn text_encoding_matches_the_text_form() {
+    for packet in server_packets() {
+        assert_eq!(
+            packet.to_binary(Encoding::Text).unwrap(),
+            packet.to_text().unwrap().into_bytes()
+        );
+    }
+}
+
+#[test]
+fn binary_frames_are_smaller() {
+    let state = &server_packets()[10];
+    let text = state.to_binary(Encoding::Text).unwrap().len();
+
+    assert!(state.to_binary(Encoding::MessagePack).unwrap().len() < text);
+    assert!(state.to_binary(Encoding::Cbor).unwrap().len() < text);
+}
+
+#[test]
+fn rejects_broken_frames() {
+    for encoding in [Encoding::MessagePack, Encoding::Cbor] {
+        assert_eq!(
+            ClientPacket::from_binary(&[0xff, 0x00], encoding).err(),
+            Some(PacketError::InvalidPacket)
+        );
+        assert_eq!(
+            ClientPacket::from_binary(
+                &ServerPacket::EndMove.to_binary(encoding).unwrap(),
+                encoding
+            )
+            .err(),
+            Some(PacketError::UnknownPacket)
+        );
+        assert_eq!(
+            ClientPacket::from_binary(&encode::<()>(encoding, "joinGame", None).unwrap(), encoding)
+                .err(),
+            Some(PacketError::InvalidPacket)
+        );
+        assert_eq!(
+            ClientPacket::from_binary(&encode(encoding, "joinGame", Some(&42)).unwrap(), encoding)
+                .err(),
+            Some(PacketError::InvalidPacket)
+        );
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "typescript")]
+
+use packets::typescript::bindings;
+
+const PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../web/utils/type/packets.ts"
+);
+
+#[test]
+fn web_types_are_up_to_date() {
+    let committed = std::fs::read_to_string(PATH).unwrap();
+    assert!(
+        committed == bindings(),
+        "web/utils/type/packets.ts is outdated, run `cargo run -p packets --bin typescript > web/utils/type/packets.ts`"
+    );
+}
+
+#[test]
+fn packets_are_declared_with_their_content() {
+    let bindings = bindings();
+
+    for variant in [
+        r#"{ name: "gameState"; content: GameState }"#,
+        r#"{ name: "endMove" }"#,
+        r#"{ name: "moveMisterX"; content: Array<MoveMisterXPacket> }"#,
+        r#"{ name: "resync" }"#,
+    ] {
+        assert!(bindings.contains(variant), "{variant}");
+    }
+    for declaration in [
+        "export type GameState = ",
+        "export type StateChange = ",
+        r#"export type Role = "detective" | "mister_x";"#,
+        "export type JsonValue = ",
+    ] {
+        assert!(bindings.contains(declaration), "{declaration}");
+    }
+
+    // fields with defaults can be left out by clients
+    assert!(bindings.contains("legal_moves?: boolean"));
+    assert!(bindings.contains("map?: string,"));
+}
@@ -1,82 +1,463 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{DeriveInput, Token, parse_macro_input, punctuated::Punctuated};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Fields, Ident, LitStr, Path, Token, Type, parse_macro_input,
+    punctuated::Punctuated,
+};
 
-#[proc_macro_derive(Packets)]
+/// generates the wire format of the packets of an enum
+///
+/// `#[packet(crate = "...")]` on the enum sets the path of the `packets` crate, `::packets` by
+/// default, `#[packet(name = "...")]` on a variant the name it is sent with. The TypeScript and
+/// JSON Schema functions are only generated if the deriving crate has the `typescript` and
+/// `schema` features.
+#[proc_macro_derive(Packets, attributes(packet))]
 pub fn packets(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
 
-    let data = match input.data {
-        syn::Data::Enum(data) => data,
-        _ => panic!("Unsupported data type"),
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// a variant with the name it is sent with
+struct Variant<'a> {
+    ident: &'a Ident,
+    name: String,
+    docs: String,
+    shape: Shape<'a>,
+}
+
+enum Shape<'a> {
+    Unit,
+    /// the content is the field itself
+    Newtype(&'a Type),
+    /// the content is an array of the fields
+    Tuple(Vec<&'a syn::Field>),
+    /// the content is an object of the fields
+    Struct(Vec<&'a syn::Field>),
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        Data::Struct(data) => {
+            return Err(syn::Error::new_spanned(
+                data.struct_token,
+                "`Packets` can only be derived for enums",
+            ));
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "`Packets` can only be derived for enums",
+            ));
+        }
     };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`Packets` can't be derived for generic enums",
+        ));
+    }
+
+    let krate = crate_path(&input.attrs)?;
+
+    let mut variants: Vec<Variant> = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for variant in &data.variants {
+        let result = packet_name(variant).and_then(|packet_name| {
+            match variants.iter().find(|other| other.name == packet_name) {
+                Some(other) => Err(syn::Error::new_spanned(
+                    &variant.ident,
+                    format!(
+                        "packet name `{packet_name}` is already used by `{}`",
+                        other.ident
+                    ),
+                )),
+                None => Ok(packet_name),
+            }
+        });
+
+        match result {
+            Ok(packet_name) => variants.push(Variant {
+                ident: &variant.ident,
+                name: packet_name,
+                docs: doc_comment(&variant.attrs),
+                shape: match &variant.fields {
+                    Fields::Unit => Shape::Unit,
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        Shape::Newtype(&fields.unnamed[0].ty)
+                    }
+                    Fields::Unnamed(fields) => Shape::Tuple(fields.unnamed.iter().collect()),
+                    Fields::Named(fields) => Shape::Struct(fields.named.iter().collect()),
+                },
+            }),
+            Err(err) => match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let mut name_map: Punctuated<_, Token![,]> = Punctuated::new();
+    let mut from_content_map: Punctuated<_, Token![,]> = Punctuated::new();
+    let mut to_binary_map: Punctuated<_, Token![,]> = Punctuated::new();
+    let mut content_names = Vec::new();
+    let mut typescript_variants = Vec::new();
+    let mut typescript_dependencies = Vec::new();
+    let mut schemas = Vec::new();
+
+    for Variant {
+        ident,
+        name: package_name,
+        docs,
+        shape,
+    } in &variants
+    {
+        match shape {
+            Shape::Unit => {
+                name_map.push(quote! {
+                  #name::#ident => #package_name
+                });
+
+                from_content_map.push(quote! {
+                  #package_name => {Ok(#name::#ident)}
+                });
 
-    let mut from_string_map: Punctuated<_, Token![,]> = Punctuated::new();
-    let mut to_string_map: Punctuated<_, Token![,]> = Punctuated::new();
-
-    for ele in &data.variants {
-        let ident = &ele.ident;
-        let package_name = format!(
-            "{}{}",
-            &ident.to_string()[..1].to_lowercase(),
-            &ident.to_string()[1..]
-        );
-        let package_name_with_brackets = format!("[{}]", package_name);
-
-        if ele.fields.is_empty() {
-            from_string_map.push(quote! {
-              #package_name_with_brackets => {Ok(#name::#ident)}
-            });
-
-            to_string_map.push(quote! {
-              #name::#ident => (#package_name, None)
-            });
-        } else {
-            from_string_map.push(quote! {
-              #package_name_with_brackets => {Ok(#name::#ident(get_content(content)?)) }
-            });
-
-            to_string_map.push(quote! {
-              #name::#ident(content) => (#package_name, Some(serde_json::to_string(content).unwrap()))
-            });
+                to_binary_map.push(quote! {
+                  #name::#ident => #krate::encoding::encode::<()>(encoding, #package_name, None)
+                });
+
+                let variant = format!("{{ name: \"{package_name}\" }}");
+                typescript_variants.push(quote! { #variant.to_string() });
+
+                schemas.push(quote! {
+                  #krate::docs::PacketSchema::new(#package_name, #docs, #krate::__private::schemars::json_schema!({ "type": "null" }))
+                });
+            }
+            Shape::Newtype(content_type) => {
+                content_names.push(package_name);
+
+                name_map.push(quote! {
+                  #name::#ident(_) => #package_name
+                });
+
+                from_content_map.push(quote! {
+                  #package_name => {Ok(#name::#ident(content.ok_or(#krate::PacketError::InvalidPacket)?.deserialize()?))}
+                });
+
+                to_binary_map.push(quote! {
+                  #name::#ident(content) => #krate::encoding::encode(encoding, #package_name, Some(content))
+                });
+
+                let variant = format!("{{{{ name: \"{package_name}\"; content: {{}} }}}}");
+                typescript_variants.push(quote! {
+                  format!(#variant, <#content_type as #krate::__private::ts_rs::TS>::name())
+                });
+                typescript_dependencies.push(quote! {
+                  visitor.visit::<#content_type>();
+                  <#content_type as #krate::__private::ts_rs::TS>::visit_generics(visitor);
+                });
+
+                schemas.push(quote! {
+                  #krate::docs::PacketSchema::new(#package_name, #docs, #krate::__private::schemars::schema_for!(#content_type))
+                });
+            }
+            Shape::Tuple(fields) | Shape::Struct(fields) => {
+                content_names.push(package_name);
+
+                let is_struct = matches!(shape, Shape::Struct(_));
+                let bindings: Vec<_> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| field.ident.clone().unwrap_or(format_ident!("field_{i}")))
+                    .collect();
+                let owned = fields_struct(&krate, fields, is_struct);
+                let pattern = if is_struct {
+                    quote! { #name::#ident { #(#bindings),* } }
+                } else {
+                    quote! { #name::#ident( #(#bindings),* ) }
+                };
+                let fields_pattern = if is_struct {
+                    quote! { Fields { #(#bindings),* } }
+                } else {
+                    quote! { Fields( #(#bindings),* ) }
+                };
+
+                name_map.push(if is_struct {
+                    quote! { #name::#ident { .. } => #package_name }
+                } else {
+                    quote! { #name::#ident(..) => #package_name }
+                });
+
+                from_content_map.push(quote! {
+                  #package_name => {
+                    #owned
+                    let #fields_pattern = content.ok_or(#krate::PacketError::InvalidPacket)?.deserialize()?;
+                    Ok(#pattern)
+                  }
+                });
+
+                let serialized = if is_struct {
+                    let borrowed = borrowed_fields_struct(&krate, fields);
+                    quote! {{
+                      #borrowed
+                      #krate::encoding::encode(encoding, #package_name, Some(&Fields { #(#bindings),* }))
+                    }}
+                } else {
+                    quote! {
+                      #krate::encoding::encode(encoding, #package_name, Some(&( #(#bindings),* )))
+                    }
+                };
+                to_binary_map.push(quote! {
+                  #pattern => #serialized
+                });
+
+                let variant = format!("{{{{ name: \"{package_name}\"; content: {{}} }}}}");
+                typescript_variants.push(quote! {{
+                  #owned
+                  format!(#variant, <Fields as #krate::__private::ts_rs::TS>::inline().replace(", }", " }"))
+                }});
+                typescript_dependencies.push(quote! {{
+                  #owned
+                  <Fields as #krate::__private::ts_rs::TS>::visit_dependencies(visitor);
+                }});
+
+                schemas.push(quote! {{
+                  #owned
+                  #krate::docs::PacketSchema::new(#package_name, #docs, #krate::__private::schemars::schema_for!(Fields))
+                }});
+            }
         }
     }
 
-    let expended = quote! {
-       impl #name {
-        pub fn from_string(message: &str) -> Result<Self, PacketError> {
-          let mut split = message.splitn(2, " ");
+    Ok(quote! {
+      impl #name {
+        pub fn from_string(message: &str) -> Result<Self, #krate::PacketError> {
+          let (name, content) = match message.split_once(' ') {
+            Some((name, content)) => (name, Some(content)),
+            None => (message, None),
+          };
+          let name = name
+            .strip_prefix('[')
+            .and_then(|name| name.strip_suffix(']'))
+            .ok_or(#krate::PacketError::UnknownPacket)?;
 
-          let name = split.next().unwrap().to_string();
-          let content = split.next().map(|content| content.to_string());
+          // the content of packets without one is ignored
+          const WITH_CONTENT: &[&str] = &[#(#content_names),*];
+          let content = match content {
+            Some(content) if WITH_CONTENT.contains(&name) => Some(#krate::encoding::Content::Json(
+              #krate::__private::serde_json::from_str(content)
+                .map_err(|_| #krate::PacketError::InvalidPacket)?,
+            )),
+            _ => None,
+          };
+          Self::from_content(name, content)
+        }
 
-          fn get_content<T: DeserializeOwned>(content: Option<String>) -> Result<T, PacketError> {
-              serde_json::from_str(&content.ok_or(PacketError::InvalidPacket)?)
-                  .map_err(|_| PacketError::InvalidPacket)
+        /// name of the packet as it is sent, without brackets
+        pub fn name(&self) -> &'static str {
+          match self {
+            #name_map
           }
+        }
 
-          match name.as_str() {
-            #from_string_map
-            _ => Err(PacketError::UnknownPacket)
+        pub fn from_content(name: &str, content: Option<#krate::encoding::Content>) -> Result<Self, #krate::PacketError> {
+          match name {
+            #from_content_map
+            _ => Err(#krate::PacketError::UnknownPacket)
           }
         }
-      }
 
-      impl Display for #name {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-          let (name, content) = match self {
-            #to_string_map
-          };
+        pub fn from_binary(bytes: &[u8], encoding: #krate::encoding::Encoding) -> Result<Self, #krate::PacketError> {
+          let (name, content) = #krate::encoding::decode(bytes, encoding)?;
+          Self::from_content(&name, content)
+        }
 
-          if let Some(content) = content {
-              f.write_fmt(format_args!("[{}] {}", name, content))
-          } else {
-              f.write_fmt(format_args!("[{}]", name))
+        /// the text encoding gives the same bytes as `to_text`
+        pub fn to_binary(&self, encoding: #krate::encoding::Encoding) -> Result<Vec<u8>, #krate::PacketError> {
+          match self {
+            #to_binary_map
           }
         }
+
+        /// `[name] content`
+        pub fn to_text(&self) -> Result<String, #krate::PacketError> {
+          let bytes = self.to_binary(#krate::encoding::Encoding::Text)?;
+          Ok(String::from_utf8(bytes).expect("text encoding is utf-8"))
+        }
+
+        /// union of `{ name, content }` objects, the content types are declared separately
+        #[cfg(feature = "typescript")]
+        pub fn typescript() -> String {
+          let variants: Vec<String> = vec![#(#typescript_variants),*];
+          format!("export type {} =\n\t| {};\n", stringify!(#name), variants.join("\n\t| "))
+        }
+
+        /// visits the content types of all packets
+        #[cfg(feature = "typescript")]
+        pub fn visit_typescript_dependencies(visitor: &mut impl #krate::__private::ts_rs::TypeVisitor) {
+          #(#typescript_dependencies)*
+        }
+
+        /// JSON Schema of the content of every packet, packets without content have `null`
+        #[cfg(feature = "schema")]
+        pub fn json_schemas() -> Vec<#krate::docs::PacketSchema> {
+          vec![#(#schemas),*]
+        }
       }
+    })
+}
+
+/// the fields of a variant as their own type, to deserialize them and describe them in TypeScript
+/// and JSON Schema
+fn fields_struct(krate: &Path, fields: &[&syn::Field], is_struct: bool) -> TokenStream2 {
+    let attrs: Vec<_> = fields
+        .iter()
+        .map(|field| forwarded_attrs(&field.attrs))
+        .collect();
+    let types = fields.iter().map(|field| &field.ty);
+    let serde_crate = private_path(krate, "serde");
+    let schemars_crate = private_path(krate, "schemars");
+    let ts_crate = private_path(krate, "ts_rs");
+    let derive = quote! {
+      #[derive(#krate::__private::serde::Deserialize)]
+      #[serde(crate = #serde_crate)]
+      #[cfg_attr(feature = "schema", derive(#krate::__private::schemars::JsonSchema), schemars(crate = #schemars_crate))]
+      #[cfg_attr(feature = "typescript", derive(#krate::__private::ts_rs::TS), ts(crate = #ts_crate))]
+      // only constructed when deserializing
+      #[allow(dead_code)]
     };
 
-    TokenStream::from(expended)
+    if is_struct {
+        let idents = fields.iter().map(|field| &field.ident);
+        quote! {
+          #derive
+          struct Fields { #(#(#attrs)* #idents: #types),* }
+        }
+    } else {
+        quote! {
+          #derive
+          struct Fields( #(#(#attrs)* #types),* );
+        }
+    }
+}
+
+/// serializes the fields of a struct variant without moving them out of the packet
+fn borrowed_fields_struct(krate: &Path, fields: &[&syn::Field]) -> TokenStream2 {
+    let attrs = fields.iter().map(|field| forwarded_attrs(&field.attrs));
+    let idents = fields.iter().map(|field| &field.ident);
+    let types = fields.iter().map(|field| &field.ty);
+    let serde_crate = private_path(krate, "serde");
+
+    quote! {
+      #[derive(#krate::__private::serde::Serialize)]
+      #[serde(crate = #serde_crate)]
+      struct Fields<'a> { #(#(#attrs)* #idents: &'a #types),* }
+    }
+}
+
+/// a dependency re-exported for the generated code, as the string the `crate` attributes of
+/// serde, schemars and ts-rs expect
+fn private_path(krate: &Path, dependency: &str) -> LitStr {
+    let krate = quote!(#krate).to_string().replace(' ', "");
+    LitStr::new(
+        &format!("{krate}::__private::{dependency}"),
+        Span::call_site(),
+    )
+}
+
+/// doc comments and serde attributes of fields also apply to their content
+fn forwarded_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("serde"))
+        .collect()
+}
+
+/// `#[packet(crate = "...")]` of the enum, `::packets` by default
+fn crate_path(attrs: &[Attribute]) -> syn::Result<Path> {
+    let mut path = syn::parse_quote!(::packets);
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("crate") {
+                return Err(meta.error("unknown packet attribute, expected `crate = \"...\"`"));
+            }
+
+            path = meta.value()?.parse::<LitStr>()?.parse()?;
+            Ok(())
+        })?;
+    }
+
+    Ok(path)
+}
+
+/// `#[packet(name = "...")]` or the variant with a lowercase first letter
+fn packet_name(variant: &syn::Variant) -> syn::Result<String> {
+    let mut name = None;
+
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("packet"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("name") {
+                return Err(meta.error("unknown packet attribute, expected `name = \"...\"`"));
+            }
+
+            let value: LitStr = meta.value()?.parse()?;
+            let value_name = value.value();
+            // `[`, `]` and whitespace end the name, `#` starts the request id
+            if value_name.is_empty()
+                || value_name
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '[' | ']' | '#'))
+            {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    "packet names can't be empty or contain whitespace, `[`, `]` or `#`",
+                ));
+            }
+
+            name = Some(value_name);
+            Ok(())
+        })?;
+    }
+
+    Ok(name.unwrap_or_else(|| {
+        let ident = variant.ident.to_string();
+        // the first letter may take more than one byte
+        let mut chars = ident.chars();
+        let first = chars.next().expect("identifiers aren't empty");
+        format!("{}{}", first.to_lowercase(), chars.as_str())
+    }))
+}
+
+/// doc comment of a variant, lines joined with spaces
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -0,0 +1,10 @@
+use packets_derive::Packets;
+
+#[derive(Packets)]
+enum Packet {
+    Ping,
+    #[packet(name = "ping")]
+    Pong,
+}
+
+fn main() {}
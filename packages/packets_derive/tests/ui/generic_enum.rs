@@ -0,0 +1,8 @@
+use packets_derive::Packets;
+
+#[derive(Packets)]
+enum Packet<T> {
+    Content(T),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use packets_derive::Packets;
+
+#[derive(Packets)]
+enum Packet {
+    #[packet(name = "ping#1")]
+    Ping,
+    #[packet(name = 42)]
+    Pong,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use packets_derive::Packets;
+
+#[derive(Packets)]
+struct Packet {
+    name: String,
+}
+
+fn main() {}
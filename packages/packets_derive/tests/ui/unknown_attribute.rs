@@ -0,0 +1,9 @@
+use packets_derive::Packets;
+
+#[derive(Packets)]
+enum Packet {
+    #[packet(rename = "ping")]
+    Ping,
+}
+
+fn main() {}
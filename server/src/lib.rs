@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::Arc};
+use std::sync::Arc;
 
 use axum::{
     Json, Router,
@@ -17,25 +17,47 @@ use tower_http::{
 use tracing::Level;
 
 use crate::services::{
-    data::DataServiceHandle,
+    data::MapRegistryHandle,
     game::{GameService, GameServiceHandle},
     lobby::{LobbyService, LobbyServiceHandle},
+    persistence::{PersistenceService, PersistenceServiceHandle},
+    replay::ReplayStoreHandle,
     ws_connection::{WsConnectionService, WsConnectionServiceHandle},
 };
 
 mod routes;
 pub mod services;
+pub mod settings;
+
+pub use settings::{Settings, SettingsHandle};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum AppError {
     #[error("Failed to read file at {0}")]
     FailedToReadFile(String),
+    #[error("{0} not found")]
+    NotFound(String),
+    /// the reason is only logged, it may contain details of the storage backend
+    #[error("replay store failed")]
+    ReplayStore,
+    #[error("{0}")]
+    InvalidQuery(String),
+}
+
+impl AppError {
+    pub fn replay_store(err: impl std::fmt::Display) -> Self {
+        tracing::error!("replay store failed: {err}");
+        AppError::ReplayStore
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = match &self {
             AppError::FailedToReadFile(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::ReplayStore => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
         };
 
         let body = Json(json!({ "error": self.to_string() }));
@@ -43,24 +65,20 @@ impl IntoResponse for AppError {
     }
 }
 
-pub struct Settings {
-    pub replay_dir: PathBuf,
-}
-
-pub type SettingsHandle = Arc<Settings>;
-
 #[derive(Clone)]
 pub struct AppState {
-    data: DataServiceHandle,
+    maps: MapRegistryHandle,
     lobby: LobbyServiceHandle,
     game: GameServiceHandle,
     ws_connection: WsConnectionServiceHandle,
+    persistence: PersistenceServiceHandle,
+    replay: ReplayStoreHandle,
     settings: SettingsHandle,
 }
 
-impl FromRef<AppState> for DataServiceHandle {
+impl FromRef<AppState> for MapRegistryHandle {
     fn from_ref(input: &AppState) -> Self {
-        input.data.clone()
+        input.maps.clone()
     }
 }
 
@@ -82,31 +100,59 @@ impl FromRef<AppState> for WsConnectionServiceHandle {
     }
 }
 
+impl FromRef<AppState> for PersistenceServiceHandle {
+    fn from_ref(input: &AppState) -> Self {
+        input.persistence.clone()
+    }
+}
+
+impl FromRef<AppState> for ReplayStoreHandle {
+    fn from_ref(input: &AppState) -> Self {
+        input.replay.clone()
+    }
+}
+
 impl FromRef<AppState> for SettingsHandle {
     fn from_ref(input: &AppState) -> Self {
         input.settings.clone()
     }
 }
 
-pub fn app(data_service: DataServiceHandle, settings: SettingsHandle) -> Router {
+pub fn app(
+    maps: MapRegistryHandle,
+    replay_store: ReplayStoreHandle,
+    settings: SettingsHandle,
+) -> Router {
     let cors_layer = CorsLayer::new()
         .allow_headers(Any)
         .allow_origin(Any)
         .allow_methods(Any);
 
     let ws_connection = Arc::new(Mutex::new(WsConnectionService::default()));
+    let persistence = Arc::new(PersistenceService::new(&settings.storage.data_dir));
 
     let state = AppState {
-        data: data_service.clone(),
-        lobby: Arc::new(Mutex::new(LobbyService::default())),
-        ws_connection: ws_connection.clone(),
+        maps: maps.clone(),
+        lobby: Arc::new(Mutex::new(LobbyService::new(
+            maps.clone(),
+            persistence.clone(),
+            settings.limits.max_lobbies,
+        ))),
+        ws_connection,
         game: Arc::new(Mutex::new(GameService::new(
-            data_service.clone(),
-            ws_connection.clone(),
+            maps,
+            persistence.clone(),
+            replay_store.clone(),
+            settings.limits.max_spectators,
         ))),
+        persistence,
+        replay: replay_store,
         settings: settings.clone(),
     };
 
+    tokio::spawn(routes::game::restore_bots(state.clone()));
+    tokio::spawn(routes::game::expire_restored_lobbies(state.clone()));
+
     Router::new()
         .merge(routes::routes(state))
         .layer(ServiceBuilder::new().layer(cors_layer))
@@ -1,18 +1,49 @@
-use server::{Settings, app, services::data::service::DataService};
-use std::{env, sync::Arc};
+use server::{
+    Settings, app,
+    services::{
+        data::{MapRegistry, service::DataService},
+        replay::{ReplayStoreHandle, filesystem::FilesystemReplayStore, sqlite::SqliteReplayStore},
+    },
+};
+use std::{env, process, sync::Arc};
 use tracing::info;
 
 #[tokio::main]
 async fn main() {
+    dotenv::dotenv().ok();
+
+    let config_path = env::var("CONFIG").unwrap_or_else(|_| "./config.toml".to_string());
+    let settings = match Settings::load(&config_path, |name| env::var(name).ok()) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("invalid configuration: {err}");
+            process::exit(1);
+        }
+    };
+
     tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
+        .with_max_level(settings.log_level().unwrap())
         .init();
 
-    dotenv::dotenv().ok();
+    let mut maps = MapRegistry::new(Arc::new(DataService::london(settings.rules.clone())));
+    if let Err(err) = maps.load_dir(&settings.storage.maps_dir) {
+        eprintln!("invalid map: {err}");
+        process::exit(1);
+    }
+    info!("loaded maps {:?}", maps.ids());
 
-    let port = env::var("PORT").unwrap_or_else(|_| "8081".to_string());
+    let replay_store: ReplayStoreHandle = match &settings.storage.replay_database {
+        Some(path) => match SqliteReplayStore::open(path) {
+            Ok(store) => Arc::new(store),
+            Err(err) => {
+                eprintln!("can't open replay database {}: {err}", path.display());
+                process::exit(1);
+            }
+        },
+        None => Arc::new(FilesystemReplayStore::new(&settings.storage.replay_dir)),
+    };
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port))
+    let listener = tokio::net::TcpListener::bind(settings.server.address)
         .await
         .unwrap();
 
@@ -20,12 +51,7 @@ async fn main() {
 
     axum::serve(
         listener,
-        app(
-            Arc::new(DataService),
-            Arc::new(Settings {
-                replay_dir: "./replays".into(),
-            }),
-        ),
+        app(Arc::new(maps), replay_store, Arc::new(settings)),
     )
     .await
     .unwrap();
@@ -1,92 +1,210 @@
-use std::ops::Not;
-
 use axum::{
-    Router,
+    Json, Router,
     extract::{
         State, WebSocketUpgrade,
-        ws::{Message, WebSocket},
+        ws::{CloseFrame, Message, WebSocket, close_code},
     },
     response::IntoResponse,
-    routing::any,
+    routing::{any, get, post},
+};
+use packets::{
+    AckPacket, ClientPacket, ErrorCode, ErrorPacket, GamePacket, ReceivedPacket, ServerPacket,
+    ToErrorCode, WelcomePacket,
+    encoding::Encoding,
+    version::{self, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, VersionError},
 };
-use game::{Game, GameError, event::Role};
-use packets::{ClientPacket, GamePacket, ServerPacket};
+use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::{
+    mpsc::{self, Sender},
+    oneshot, watch,
+};
 use uuid::Uuid;
 
 use crate::{
     AppState, SettingsHandle,
     services::{
-        game::{GameEventListener, GameServiceError, GameServiceHandle},
-        lobby::{LobbyServiceError, LobbyServiceHandle},
+        data::{DEFAULT_MAP, MapRegistryHandle},
+        game::{GameHandle, GameServiceError, GameServiceHandle, Spectator},
+        lobby::{
+            LobbyId, LobbyInfo, LobbyServiceError, LobbyServiceHandle, PlayerOptions, Settings,
+        },
+        persistence::PersistenceServiceHandle,
         ws_connection::WsConnectionServiceHandle,
     },
 };
 
 use futures_util::{sink::SinkExt, stream::StreamExt};
 
+mod bot;
+mod replay;
+mod validate;
+
+pub use bot::restore_bots;
+
+/// how long players of lobbies restored from a previous run have to resume them
+const RESTORED_LOBBY_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 pub fn routes(state: AppState) -> Router {
     Router::new()
         .route("/ws", any(ws_handler))
+        .route("/bots", get(get_all_bots))
+        .route("/lobbies", get(get_public_lobbies))
+        .route("/replays", get(replay::get_replays))
+        .route("/replays/{id}", get(replay::get_replay))
+        .route("/validate", post(validate::validate_move))
         .with_state(state)
 }
 
+/// removes the restored lobbies none of whose players came back in time
+pub async fn expire_restored_lobbies(state: AppState) {
+    let lobby_ids = state.lobby.lock().await.lobby_ids();
+    tokio::time::sleep(RESTORED_LOBBY_TIMEOUT).await;
+
+    for lobby_id in lobby_ids {
+        remove_if_abandoned(&state.lobby, &state.ws_connection, &lobby_id).await;
+    }
+}
+
+/// removes the lobby once no human player is connected to it, along with the connections of its bots
+async fn remove_if_abandoned(
+    lobby_service: &LobbyServiceHandle,
+    ws_connection_service: &WsConnectionServiceHandle,
+    lobby_id: &LobbyId,
+) {
+    let mut ref_lobby_service = lobby_service.lock().await;
+    let mut connections = ws_connection_service.lock().await;
+
+    let Ok(lobby) = ref_lobby_service.get_lobby(lobby_id) else {
+        return;
+    };
+    if lobby
+        .players
+        .iter()
+        .any(|player| player.bot.is_none() && connections.is_connected(player.uuid))
+    {
+        return;
+    }
+
+    if let Some(lobby) = ref_lobby_service.remove_lobby(lobby_id) {
+        for player in lobby.players.iter().filter(|player| player.bot.is_some()) {
+            connections.remove_connection(player.uuid);
+        }
+    }
+}
+
+async fn get_public_lobbies(
+    State(lobby_service): State<LobbyServiceHandle>,
+) -> Json<Vec<LobbyInfo>> {
+    Json(lobby_service.lock().await.public_lobbies())
+}
+
+async fn get_all_bots() -> Json<&'static [&'static str]> {
+    Json(bot::BOT_NAMES)
+}
+
 async fn ws_handler(
     ws: WebSocketUpgrade,
+    State(maps): State<MapRegistryHandle>,
     State(lobby_service): State<LobbyServiceHandle>,
     State(game_service): State<GameServiceHandle>,
     State(ws_connection_service): State<WsConnectionServiceHandle>,
+    State(persistence): State<PersistenceServiceHandle>,
     State(settings): State<SettingsHandle>,
 ) -> impl IntoResponse {
     ws.on_upgrade(|socket| {
         handle_socket(
             socket,
+            maps,
             lobby_service,
             game_service,
             ws_connection_service,
+            persistence,
             settings,
         )
     })
 }
 async fn handle_socket(
     socket: WebSocket,
+    maps: MapRegistryHandle,
     lobby_service: LobbyServiceHandle,
     game_service: GameServiceHandle,
     ws_connection_service: WsConnectionServiceHandle,
+    persistence: PersistenceServiceHandle,
     settings: SettingsHandle,
 ) {
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
-    let (tx, mut rx) = mpsc::channel(16);
+    let (tx, mut rx) = mpsc::channel::<ServerPacket>(16);
+    let own_sender = tx.clone();
+    let (encoding_tx, encoding_rx) = watch::channel(Encoding::Text);
+    let (replaced_tx, mut replaced_rx) = oneshot::channel();
 
     let mut send_task = tokio::spawn(async move {
-        while let Some(packet) = rx.recv().await {
-            let msg = ServerPacket::to_string(&packet);
-            let _ = ws_sender.send(Message::text(msg)).await;
+        loop {
+            let packet = tokio::select! {
+                packet = rx.recv() => match packet {
+                    Some(packet) => packet,
+                    None => break,
+                },
+                Ok(()) = &mut replaced_rx => {
+                    let _ = ws_sender
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::NORMAL,
+                            reason: "session resumed on another connection".into(),
+                        })))
+                        .await;
+                    break;
+                }
+            };
+
+            let msg = match *encoding_rx.borrow() {
+                Encoding::Text => packet.to_text().map(Message::text),
+                encoding => packet.to_binary(encoding).map(Message::binary),
+            };
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(err) => {
+                    tracing::error!(packet = packet.name(), "{err}");
+                    continue;
+                }
+            };
+            let _ = ws_sender.send(msg).await;
         }
     });
 
     let mut recv_task = {
+        let lobby_service = lobby_service.clone();
+        let ws_connection_service = ws_connection_service.clone();
+
         tokio::spawn(async move {
             let uuid = Uuid::new_v4();
 
             ws_connection_service
                 .lock()
                 .await
-                .add_connection(uuid, tx.clone());
+                .add_connection(uuid, tx.clone(), replaced_tx);
 
             let mut connection = Connection {
                 connection_id: uuid,
+                maps,
                 lobby_service,
                 game_service,
                 ws_connection_service,
                 settings,
+                // clients without `hello` predate it
+                protocol_version: MIN_PROTOCOL_VERSION,
+                encoding: encoding_tx,
             };
 
             while let Some(Ok(msg)) = ws_receiver.next().await {
-                let packet = match msg {
-                    Message::Text(t) => ClientPacket::from_string(t.as_str()),
+                let received = match msg {
+                    Message::Text(t) => {
+                        ReceivedPacket::from_text(t.as_str(), connection.protocol_version)
+                    }
+                    Message::Binary(bytes) => {
+                        ReceivedPacket::from_binary(&bytes, *connection.encoding.borrow())
+                    }
                     Message::Close(_) => {
                         break;
                     }
@@ -94,17 +212,46 @@ async fn handle_socket(
                         continue;
                     }
                 };
+                let id = received.id;
 
-                let packet = match packet {
+                let packet = match received.packet {
                     Ok(packet) => packet,
                     Err(err) => {
-                        tx.send(ServerPacket::from_error(err)).await.unwrap();
+                        let error = ErrorPacket::from_error(&err)
+                            .with_packet(received.name)
+                            .with_id(id);
+                        let error = version::downgrade_error(error, connection.protocol_version);
+                        // the socket is being closed if nobody receives it
+                        let _ = tx.send(ServerPacket::Error(error)).await;
                         continue;
                     }
                 };
 
-                if let Err(err) = connection.handle_client_packet(packet).await {
-                    connection.send(ServerPacket::from_error(err)).await;
+                let name = packet.name();
+                let result = connection.handle_client_packet(packet).await;
+                // acknowledged packets are only answered once their changes are stored
+                if id.is_some() {
+                    persistence.flush().await;
+                }
+
+                match result {
+                    Ok(()) => {
+                        if let Some(id) = id {
+                            connection
+                                .send(ServerPacket::Ack(AckPacket {
+                                    id,
+                                    packet: name.to_string(),
+                                }))
+                                .await;
+                        }
+                    }
+                    Err(err) => {
+                        let error = ErrorPacket::from_error(&err)
+                            .with_packet(Some(name.to_string()))
+                            .with_id(id);
+                        let error = version::downgrade_error(error, connection.protocol_version);
+                        connection.send(ServerPacket::Error(error)).await;
+                    }
                 }
             }
         })
@@ -114,6 +261,11 @@ async fn handle_socket(
         _ = &mut send_task => recv_task.abort(),
         _ = &mut recv_task => send_task.abort(),
     }
+
+    let lobby_ids = ws_connection_service.lock().await.close(&own_sender);
+    for lobby_id in lobby_ids {
+        remove_if_abandoned(&lobby_service, &ws_connection_service, &lobby_id).await;
+    }
 }
 
 #[derive(Error, Debug)]
@@ -124,29 +276,65 @@ pub enum ConnectionError {
     #[error(transparent)]
     GameService(#[from] GameServiceError),
 
-    #[error(transparent)]
-    Game(#[from] GameError),
-
     #[error("game already joined")]
     GameAlreadyJoined,
 
-    #[error("not your turn")]
-    NotAllowedForUser,
-
     #[error("not in game")]
     NotInGame,
 
     #[error("not in lobby")]
     NotInLobby,
+
+    #[error("unknown bot")]
+    UnknownBot,
+
+    #[error("no rematch available")]
+    NoRematch,
+
+    #[error("unknown session")]
+    UnknownSession,
+
+    #[error(transparent)]
+    Version(#[from] VersionError),
+}
+
+impl ToErrorCode for ConnectionError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            ConnectionError::Lobby(err) => err.code(),
+            ConnectionError::GameService(err) => err.code(),
+            ConnectionError::GameAlreadyJoined => ErrorCode::GameAlreadyJoined,
+            ConnectionError::NotInGame => ErrorCode::NotInGame,
+            ConnectionError::NotInLobby => ErrorCode::NotInLobby,
+            ConnectionError::UnknownBot => ErrorCode::UnknownBot,
+            ConnectionError::NoRematch => ErrorCode::NoRematch,
+            ConnectionError::UnknownSession => ErrorCode::UnknownSession,
+            ConnectionError::Version(err) => err.code(),
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            ConnectionError::Lobby(err) => err.details(),
+            ConnectionError::GameService(err) => err.details(),
+            ConnectionError::Version(err) => err.details(),
+            _ => None,
+        }
+    }
 }
 
 struct Connection {
     connection_id: Uuid,
 
+    maps: MapRegistryHandle,
     lobby_service: LobbyServiceHandle,
     game_service: GameServiceHandle,
     ws_connection_service: WsConnectionServiceHandle,
     settings: SettingsHandle,
+    /// negotiated with `hello`, older clients get their packets upgraded
+    protocol_version: u32,
+    /// negotiated with `hello`, read by the task sending packets
+    encoding: watch::Sender<Encoding>,
 }
 
 impl Connection {
@@ -159,7 +347,7 @@ impl Connection {
     }
 
     async fn send(&self, packet: ServerPacket) {
-        self.sender().await.send(packet).await.unwrap();
+        let _ = self.sender().await.send(packet).await;
     }
 
     async fn lobby_id(&self) -> Option<Uuid> {
@@ -186,47 +374,20 @@ impl Connection {
             .unwrap()
     }
 
-    fn assert_own_round(&self, game: &Game<GameEventListener>) -> Result<(), ConnectionError> {
-        if game
-            .event_listener()
-            .get_user_role(self.connection_id)
-            .eq(game.active_role())
-            .not()
-        {
-            return Err(ConnectionError::NotAllowedForUser);
-        }
-
-        Ok(())
-    }
-
-    fn assert_detective(&self, game: &Game<GameEventListener>) -> Result<(), ConnectionError> {
-        if matches!(
-            game.event_listener().get_user_role(self.connection_id),
-            Role::Detective
-        ) {
-            return Err(ConnectionError::NotAllowedForUser);
-        }
-
-        Ok(())
-    }
-
-    fn assert_mister_x(&self, game: &Game<GameEventListener>) -> Result<(), ConnectionError> {
-        if matches!(
-            game.event_listener().get_user_role(self.connection_id),
-            Role::MisterX
-        ) {
-            return Err(ConnectionError::NotAllowedForUser);
-        }
-
-        Ok(())
+    async fn rematch_id(&self) -> Option<Uuid> {
+        self.ws_connection_service
+            .lock()
+            .await
+            .rematch_id(self.connection_id)
+            .unwrap()
     }
 
-    async fn assert_in_game(&self) -> Result<(), ConnectionError> {
-        if self.game_id().await.is_none() {
+    async fn game(&self) -> Result<GameHandle, ConnectionError> {
+        let Some(game_id) = self.game_id().await else {
             return Err(ConnectionError::NotInGame);
-        }
+        };
 
-        Ok(())
+        Ok(self.game_service.lock().await.get_game(&game_id)?)
     }
 
     async fn assert_in_lobby(&self) -> Result<(), ConnectionError> {
@@ -239,104 +400,244 @@ impl Connection {
 
     async fn handle_client_packet(&mut self, packet: ClientPacket) -> Result<(), ConnectionError> {
         match packet {
+            ClientPacket::Hello(packet) => {
+                let protocol_version = version::negotiate(packet.protocol_version)?;
+                tracing::debug!(
+                    "client {} speaks protocol version {}",
+                    packet.client_name.as_deref().unwrap_or("unknown"),
+                    packet.protocol_version
+                );
+                self.protocol_version = protocol_version;
+                self.encoding.send_replace(packet.encoding);
+
+                self.send(ServerPacket::Welcome(WelcomePacket {
+                    protocol_version,
+                    server_protocol_version: PROTOCOL_VERSION,
+                    encoding: packet.encoding,
+                }))
+                .await;
+            }
             ClientPacket::CreateGame(packet) => {
-                let id = self
-                    .lobby_service
+                let mut ref_lobby_service = self.lobby_service.lock().await;
+                let id = ref_lobby_service.create(Settings {
+                    number_of_detectives: packet.number_of_detectives,
+                    spectators: packet.spectators,
+                    public: packet.public,
+                    map: packet.map.unwrap_or_else(|| DEFAULT_MAP.to_string()),
+                })?;
+                let code = ref_lobby_service.get_lobby(&id)?.code.clone();
+                drop(ref_lobby_service);
+
+                let _ = self
+                    .ws_connection_service
                     .lock()
                     .await
-                    .create(packet.number_of_detectives);
-                self.send(ServerPacket::Game(GamePacket { id })).await;
+                    .add_created_lobby(self.connection_id, id);
+
+                self.send(ServerPacket::Game(GamePacket { id, code })).await;
             }
             ClientPacket::JoinGame(packet) => {
                 if self.lobby_id().await.is_some() {
                     return Err(ConnectionError::GameAlreadyJoined);
                 }
 
-                self.lobby_service.lock().await.join(
+                let mut ref_lobby_service = self.lobby_service.lock().await;
+                let lobby_id = ref_lobby_service.resolve(&packet.id)?;
+                ref_lobby_service.join(
                     self.connection_id,
-                    &packet.id,
+                    &lobby_id,
                     self.sender().await,
+                    PlayerOptions {
+                        legal_moves: packet.legal_moves,
+                        state_deltas: packet.state_deltas,
+                    },
+                    None,
                 )?;
+                drop(ref_lobby_service);
 
-                self.set_lobby_id(Some(packet.id)).await;
+                self.set_lobby_id(Some(lobby_id)).await;
             }
-            ClientPacket::StartGame => {
+            ClientPacket::SpectateGame(packet) => {
+                if self.lobby_id().await.is_some() || self.game_id().await.is_some() {
+                    return Err(ConnectionError::GameAlreadyJoined);
+                }
+
+                let game = self.game_service.lock().await.get_game(&packet.id)?;
+                game.spectate(Spectator {
+                    uuid: self.connection_id,
+                    ws_sender: self.sender().await,
+                    view: packet.view,
+                })
+                .await?;
+            }
+            ClientPacket::ResumeGame(packet) => {
+                if self.lobby_id().await.is_some() || self.game_id().await.is_some() {
+                    return Err(ConnectionError::GameAlreadyJoined);
+                }
+
+                self.resume(packet.session).await?;
+            }
+            ClientPacket::AddBot(packet) => {
                 self.assert_in_lobby().await?;
 
-                let lobby_id = self.lobby_id().await.unwrap();
+                bot::add_bot(self, &packet.name, self.lobby_id().await.unwrap()).await?;
+            }
+            ClientPacket::StartGame => {
+                self.assert_in_lobby().await?;
 
-                let mut ref_lobby_service = self.lobby_service.lock().await;
-                let lobby = ref_lobby_service.get_lobby(&lobby_id)?;
+                self.start_game(self.lobby_id().await.unwrap()).await?;
+            }
+            ClientPacket::MoveMisterX(packet) => {
+                self.game()
+                    .await?
+                    .move_mister_x(
+                        self.connection_id,
+                        packet
+                            .into_iter()
+                            .map(|packet| (packet.station_id, packet.transport_type))
+                            .collect(),
+                    )
+                    .await?;
+            }
+            ClientPacket::MoveDetective(packet) => {
+                self.game()
+                    .await?
+                    .move_detective(
+                        self.connection_id,
+                        packet.color,
+                        packet.station_id,
+                        packet.transport_type,
+                    )
+                    .await?;
+            }
+            ClientPacket::Resync => {
+                self.game().await?.resync(self.connection_id).await?;
+            }
+            ClientPacket::SubmitMove => {
+                let game = self.game().await?;
 
-                self.game_service.lock().await.add_game_from_lobby(
-                    lobby,
-                    &lobby_id,
-                    self.settings.clone(),
-                )?;
+                if let Some(lobby) = game.submit_move(self.connection_id).await? {
+                    let game_id = self.game_id().await.unwrap();
+                    self.game_service.lock().await.remove_game(&game_id);
+                    let rematch_id = self.lobby_service.lock().await.add_lobby(lobby.clone());
 
-                for player in &lobby.players {
                     let mut connections = self.ws_connection_service.lock().await;
+                    for player in &lobby.players {
+                        let _ = connections.set_game_id(player.uuid, None);
+                        let _ = connections.set_rematch_id(player.uuid, Some(rematch_id));
+                    }
+                    drop(connections);
+
+                    lobby.send_state(&rematch_id).await;
+                    // every human may have left while a bot finished the game
+                    remove_if_abandoned(
+                        &self.lobby_service,
+                        &self.ws_connection_service,
+                        &rematch_id,
+                    )
+                    .await;
+                }
+            }
+            ClientPacket::Rematch => {
+                let Some(rematch_id) = self.rematch_id().await else {
+                    return Err(ConnectionError::NoRematch);
+                };
 
-                    let _ = connections.set_game_id(player.uuid, Some(lobby_id));
-                    let _ = connections.set_lobby_id(player.uuid, None);
+                let mut ref_lobby_service = self.lobby_service.lock().await;
+                let all_accepted =
+                    ref_lobby_service.accept_rematch(&rematch_id, self.connection_id)?;
+                ref_lobby_service
+                    .get_lobby(&rematch_id)?
+                    .send_state(&rematch_id)
+                    .await;
+                drop(ref_lobby_service);
+
+                if all_accepted {
+                    self.start_game(rematch_id).await?;
                 }
+            }
+        }
 
-                ref_lobby_service.close_lobby(&lobby_id);
+        Ok(())
+    }
 
-                let ref_game_service = self.game_service.lock().await;
-                let game = ref_game_service.get_game(&self.game_id().await.unwrap())?;
-                game.lock().await.start().await;
-            }
-            ClientPacket::MoveMisterX(packet) => {
-                self.assert_in_game().await?;
+    async fn start_game(&self, lobby_id: Uuid) -> Result<(), ConnectionError> {
+        let lobby = self.lobby_service.lock().await.take_lobby(&lobby_id)?;
 
-                let ref_game_service = self.game_service.lock().await;
-                let game_ref = ref_game_service.get_game(&self.game_id().await.unwrap())?;
-                let mut game = game_ref.lock().await;
+        let game = self
+            .game_service
+            .lock()
+            .await
+            .add_game_from_lobby(&lobby, &lobby_id)?;
 
-                self.assert_own_round(&game)?;
-                self.assert_detective(&game)?;
+        let mut connections = self.ws_connection_service.lock().await;
+        for player in &lobby.players {
+            let _ = connections.set_game_id(player.uuid, Some(lobby_id));
+            let _ = connections.set_lobby_id(player.uuid, None);
+            let _ = connections.set_rematch_id(player.uuid, None);
+        }
+        drop(connections);
 
-                game.move_mister_x(
-                    packet
-                        .into_iter()
-                        .map(|packet| (packet.station_id, packet.transport_type))
-                        .collect(),
-                )?;
-            }
-            ClientPacket::MoveDetective(packet) => {
-                self.assert_in_game().await?;
+        game.start().await?;
 
-                let ref_game_service = self.game_service.lock().await;
-                let game_ref = ref_game_service.get_game(&self.game_id().await.unwrap())?;
-                let mut game = game_ref.lock().await;
+        Ok(())
+    }
 
-                self.assert_own_round(&game)?;
-                self.assert_mister_x(&game)?;
+    /// continues the game or rematch lobby of a session on this connection
+    async fn resume(&mut self, session: Uuid) -> Result<(), ConnectionError> {
+        let sender = self.sender().await;
 
-                game.move_detective(packet.color, packet.station_id, packet.transport_type)
-                    .await?;
-            }
-            ClientPacket::SubmitMove => {
-                self.assert_in_game().await?;
+        let game_session = self.game_service.lock().await.find_session(&session);
+        if let Some(game_session) = game_session {
+            let game = self
+                .game_service
+                .lock()
+                .await
+                .get_game(&game_session.game_id)?;
 
-                let game_id = &self.game_id().await.unwrap();
-                let ref_game_service = self.game_service.lock().await;
-                let game_ref = ref_game_service.get_game(&self.game_id().await.unwrap())?;
-                let mut game = game_ref.lock().await;
+            self.take_over(game_session.player_id).await;
+            self.ws_connection_service
+                .lock()
+                .await
+                .set_game_id(self.connection_id, Some(game_session.game_id))
+                .unwrap();
 
-                self.assert_own_round(&game)?;
+            game.resume(self.connection_id, sender).await?;
 
-                let ended = game.end_move().await?;
-                drop(ref_game_service);
-                drop(game);
+            return Ok(());
+        }
 
-                if ended {
-                    self.game_service.lock().await.remove_game(game_id).await;
-                }
-            }
+        let lobby_session = self.lobby_service.lock().await.find_session(&session);
+        let Some((lobby_id, player_id)) = lobby_session else {
+            return Err(ConnectionError::UnknownSession);
+        };
+
+        self.take_over(player_id).await;
+
+        let mut ref_lobby_service = self.lobby_service.lock().await;
+        let lobby = ref_lobby_service.reconnect(&lobby_id, player_id, sender)?;
+
+        let mut connections = self.ws_connection_service.lock().await;
+        if lobby.rematch.is_some() {
+            let _ = connections.set_rematch_id(player_id, Some(lobby_id));
+        } else {
+            let _ = connections.set_lobby_id(player_id, Some(lobby_id));
         }
+        drop(connections);
+
+        lobby.send_state(&lobby_id).await;
 
         Ok(())
     }
+
+    /// continues as the player of a session, closing its previous connection
+    async fn take_over(&mut self, player_id: Uuid) {
+        self.ws_connection_service
+            .lock()
+            .await
+            .take_over(self.connection_id, player_id)
+            .unwrap();
+
+        self.connection_id = player_id;
+    }
 }
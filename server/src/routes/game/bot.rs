@@ -0,0 +1,266 @@
+use game::event::{GameState, Role};
+use packets::{ClientPacket, ServerPacket, encoding::Encoding, version::PROTOCOL_VERSION};
+use runtime::{Bot, GameData};
+use tokio::sync::{
+    mpsc::{self, Receiver, Sender, UnboundedReceiver},
+    oneshot, watch,
+};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    routes::game::{Connection, ConnectionError},
+    services::{
+        data::DataServiceHandle,
+        lobby::{BotSeat, LobbyId, LobbyServiceError, PlayerOptions},
+    },
+};
+
+enum BotEvent {
+    Turn(Role, GameState),
+    Rematch,
+}
+
+/// names accepted by the `addBot` packet
+pub const BOT_NAMES: &[&str] = &["random"];
+
+/// adds a bot seat to the lobby which plays through the same packets as a websocket connection
+pub async fn add_bot(
+    connection: &Connection,
+    name: &str,
+    lobby_id: LobbyId,
+) -> Result<(), ConnectionError> {
+    if !BOT_NAMES.contains(&name) {
+        return Err(ConnectionError::UnknownBot);
+    }
+
+    let map_id = connection
+        .lobby_service
+        .lock()
+        .await
+        .get_lobby(&lobby_id)?
+        .settings
+        .map
+        .clone();
+    let map = connection
+        .maps
+        .get(&map_id)
+        .ok_or(LobbyServiceError::UnknownMap)?;
+
+    let uuid = Uuid::new_v4();
+    let (tx, rx) = mpsc::channel(16);
+
+    connection.lobby_service.lock().await.join(
+        uuid,
+        &lobby_id,
+        tx.clone(),
+        PlayerOptions::default(),
+        Some(name.to_string()),
+    )?;
+
+    let mut connections = connection.ws_connection_service.lock().await;
+    connections.add_connection(uuid, tx, oneshot::channel().0);
+    let _ = connections.set_lobby_id(uuid, Some(lobby_id));
+    drop(connections);
+
+    spawn_bot(bot_connection(connection, uuid), name, map, rx)
+}
+
+/// starts the bots of the lobbies and games restored from a previous run again
+pub async fn restore_bots(state: AppState) {
+    // only a template for the connections of the bots
+    let connection = Connection {
+        connection_id: Uuid::nil(),
+        maps: state.maps,
+        lobby_service: state.lobby,
+        game_service: state.game,
+        ws_connection_service: state.ws_connection,
+        settings: state.settings,
+        protocol_version: PROTOCOL_VERSION,
+        encoding: watch::Sender::new(Encoding::Text),
+    };
+
+    let lobby_seats = connection.lobby_service.lock().await.bot_seats();
+    for seat in lobby_seats {
+        let (tx, rx) = mpsc::channel(16);
+        if let Err(err) = restore_bot(&connection, &seat, tx.clone(), rx).await {
+            warn!(
+                "can't restore bot {} of lobby {}: {err}",
+                seat.player_id, seat.lobby_id
+            );
+            continue;
+        }
+
+        let mut ref_lobby_service = connection.lobby_service.lock().await;
+        let Ok(lobby) = ref_lobby_service.reconnect(&seat.lobby_id, seat.player_id, tx.clone())
+        else {
+            continue;
+        };
+
+        let mut connections = connection.ws_connection_service.lock().await;
+        if lobby.rematch.is_some() {
+            let _ = connections.set_rematch_id(seat.player_id, Some(seat.lobby_id));
+            // lets the bot accept the rematch if it did not before
+            let _ = tx.try_send(ServerPacket::LobbyState(lobby.state(&seat.lobby_id)));
+        } else {
+            let _ = connections.set_lobby_id(seat.player_id, Some(seat.lobby_id));
+        }
+    }
+
+    let game_seats = connection.game_service.lock().await.take_restored_bots();
+    for seat in game_seats {
+        let (tx, rx) = mpsc::channel(16);
+        if let Err(err) = restore_bot(&connection, &seat, tx.clone(), rx).await {
+            warn!(
+                "can't restore bot {} of game {}: {err}",
+                seat.player_id, seat.lobby_id
+            );
+            continue;
+        }
+
+        let _ = connection
+            .ws_connection_service
+            .lock()
+            .await
+            .set_game_id(seat.player_id, Some(seat.lobby_id));
+
+        let game = connection
+            .game_service
+            .lock()
+            .await
+            .get_game(&seat.lobby_id);
+        if let Ok(game) = game {
+            let _ = game.resume(seat.player_id, tx).await;
+        }
+    }
+}
+
+async fn restore_bot(
+    connection: &Connection,
+    seat: &BotSeat,
+    tx: Sender<ServerPacket>,
+    rx: Receiver<ServerPacket>,
+) -> Result<(), ConnectionError> {
+    let map = connection
+        .maps
+        .get(&seat.map)
+        .ok_or(LobbyServiceError::UnknownMap)?;
+
+    connection
+        .ws_connection_service
+        .lock()
+        .await
+        .add_connection(seat.player_id, tx, oneshot::channel().0);
+
+    spawn_bot(
+        bot_connection(connection, seat.player_id),
+        &seat.name,
+        map,
+        rx,
+    )
+}
+
+fn bot_connection(connection: &Connection, uuid: Uuid) -> Connection {
+    Connection {
+        connection_id: uuid,
+        maps: connection.maps.clone(),
+        lobby_service: connection.lobby_service.clone(),
+        game_service: connection.game_service.clone(),
+        ws_connection_service: connection.ws_connection_service.clone(),
+        settings: connection.settings.clone(),
+        protocol_version: PROTOCOL_VERSION,
+        encoding: watch::Sender::new(Encoding::Text),
+    }
+}
+
+fn spawn_bot(
+    connection: Connection,
+    name: &str,
+    map: DataServiceHandle,
+    rx: Receiver<ServerPacket>,
+) -> Result<(), ConnectionError> {
+    let data = GameData {
+        stations: map.get_all_stations(),
+        connections: map.get_all_connections(),
+        rounds: map.get_all_rounds(),
+    };
+
+    match name {
+        "random" => run(bot_random::Bot::new(data), connection, rx),
+        _ => return Err(ConnectionError::UnknownBot),
+    }
+
+    Ok(())
+}
+
+fn run<B: Bot + Send + 'static>(bot: B, connection: Connection, mut rx: Receiver<ServerPacket>) {
+    let uuid = connection.connection_id;
+
+    // turns are handed to a separate task so the server never blocks on a bot waiting for a lock
+    let (turn_tx, turn_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let mut role = None;
+        let mut own_turn = false;
+
+        while let Some(packet) = rx.recv().await {
+            match packet {
+                ServerPacket::GameStarted(packet) => role = Some(packet.role),
+                ServerPacket::StartMove(packet) => own_turn = role.as_ref() == Some(&packet.role),
+                ServerPacket::GameState(state) if own_turn => {
+                    own_turn = false;
+                    if let Some(role) = &role {
+                        let _ = turn_tx.send(BotEvent::Turn(role.clone(), state));
+                    }
+                }
+                // lobby states are only sent for rematches, which bots always accept
+                ServerPacket::LobbyState(state)
+                    if state
+                        .players
+                        .iter()
+                        .any(|player| player.id == uuid && !player.accepted_rematch) =>
+                {
+                    let _ = turn_tx.send(BotEvent::Rematch);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    play(bot, connection, turn_rx);
+}
+
+/// kept out of the async functions above, as awaiting `handle_client_packet` inside
+/// them would make their futures depend on themselves
+fn play<B: Bot + Send + 'static>(
+    mut bot: B,
+    mut connection: Connection,
+    mut turn_rx: UnboundedReceiver<BotEvent>,
+) {
+    tokio::spawn(async move {
+        while let Some(event) = turn_rx.recv().await {
+            let packets = match event {
+                BotEvent::Turn(Role::Detective, state) => {
+                    let mut packets = bot.next_detective_move(&state).into_packets();
+                    packets.push(ClientPacket::SubmitMove);
+                    packets
+                }
+                BotEvent::Turn(Role::MisterX, state) => vec![
+                    bot.next_mister_x_move(&state).into_packet(),
+                    ClientPacket::SubmitMove,
+                ],
+                BotEvent::Rematch => vec![ClientPacket::Rematch],
+            };
+
+            for packet in packets {
+                if let Err(err) = connection.handle_client_packet(packet).await {
+                    warn!(
+                        "bot {} sent an invalid packet: {}",
+                        connection.connection_id, err
+                    );
+                }
+            }
+        }
+    });
+}
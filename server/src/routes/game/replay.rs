@@ -0,0 +1,61 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use game::{event::Role, replay::Replay};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    AppError,
+    services::replay::{ReplayFilter, ReplayPage, ReplayStoreError, ReplayStoreHandle},
+};
+
+const DEFAULT_PER_PAGE: usize = 20;
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Deserialize)]
+pub struct ReplayQuery {
+    winner: Option<Role>,
+    /// unix timestamps in seconds
+    from: Option<u64>,
+    to: Option<u64>,
+    #[serde(default)]
+    page: usize,
+    per_page: Option<usize>,
+}
+
+pub async fn get_replays(
+    State(replay_store): State<ReplayStoreHandle>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<Json<ReplayPage>, AppError> {
+    let filter = ReplayFilter {
+        winner: query.winner,
+        from: query.from,
+        to: query.to,
+    };
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+
+    tokio::task::spawn_blocking(move || replay_store.list(&filter, query.page, per_page))
+        .await
+        .map_err(AppError::replay_store)?
+        .map(Json)
+        .map_err(AppError::replay_store)
+}
+
+pub async fn get_replay(
+    State(replay_store): State<ReplayStoreHandle>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Replay>, AppError> {
+    match tokio::task::spawn_blocking(move || replay_store.get(&id))
+        .await
+        .map_err(AppError::replay_store)?
+    {
+        Ok(replay) => Ok(Json(replay)),
+        Err(ReplayStoreError::UnknownReplay) => Err(AppError::NotFound("replay".to_string())),
+        Err(err) => Err(AppError::replay_store(err)),
+    }
+}
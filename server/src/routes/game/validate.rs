@@ -0,0 +1,112 @@
+use axum::{Json, extract::State};
+use game::{
+    Game, GameError, MoveError, Snapshot,
+    event::{EventListener, GameState, GameStateDelta, LegalMoves, Role},
+    replay::Replay,
+};
+use packets::{MoveDetectivePacket, MoveMisterXPacket};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppError,
+    services::data::{DEFAULT_MAP, MapId, MapRegistryHandle},
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposedMove {
+    Detective(MoveDetectivePacket),
+    MisterX(Vec<MoveMisterXPacket>),
+}
+
+#[derive(Deserialize)]
+pub struct ValidateRequest {
+    /// the default map if not set
+    map: Option<MapId>,
+    snapshot: Snapshot,
+    #[serde(rename = "move")]
+    proposed_move: ProposedMove,
+}
+
+#[derive(Serialize)]
+pub struct ValidateResponse {
+    legal: bool,
+    reason: Option<MoveError>,
+    /// the snapshot after the move, unchanged if the move is illegal
+    snapshot: Snapshot,
+    state: GameState,
+}
+
+/// the game is only used to check the move, nobody has to be notified
+struct NoListener;
+
+impl EventListener for NoListener {
+    async fn on_game_start(&self) {}
+    async fn on_start_round(&self, _role: &Role, _legal_moves: &LegalMoves) {}
+    async fn on_end_move(&self) {}
+    async fn on_game_ended(&self, _replay: &Replay) {}
+    async fn on_game_state_update(
+        &self,
+        _state: GameState,
+        _delta: Option<&GameStateDelta>,
+        _show_mister_x: bool,
+    ) {
+    }
+}
+
+/// checks a move against a snapshot with the rules of the game, nothing is stored
+pub async fn validate_move(
+    State(maps): State<MapRegistryHandle>,
+    Json(request): Json<ValidateRequest>,
+) -> Result<Json<ValidateResponse>, AppError> {
+    let map = maps
+        .get(request.map.as_deref().unwrap_or(DEFAULT_MAP))
+        .ok_or_else(|| AppError::NotFound("map".to_string()))?;
+    if !request.snapshot.fits(map.get_all_rounds().len()) {
+        return Err(AppError::InvalidQuery(
+            "snapshot does not fit the rounds of the map".to_string(),
+        ));
+    }
+
+    let mut game = Game::restore(
+        request.snapshot.clone(),
+        map.get_all_connections(),
+        map.get_all_rounds(),
+        NoListener,
+    );
+
+    let result = match request.proposed_move {
+        ProposedMove::Detective(packet) => {
+            game.move_detective(packet.color, packet.station_id, packet.transport_type)
+                .await
+        }
+        ProposedMove::MisterX(packets) => game.move_mister_x(
+            packets
+                .into_iter()
+                .map(|packet| (packet.station_id, packet.transport_type))
+                .collect(),
+        ),
+    };
+
+    let reason = match result {
+        Ok(()) => None,
+        Err(GameError::InvalidMove(reason)) => Some(reason),
+        Err(GameError::NotAllMoved) => unreachable!("only returned when ending a move"),
+    };
+
+    if reason.is_some() {
+        game = Game::restore(
+            request.snapshot,
+            map.get_all_connections(),
+            map.get_all_rounds(),
+            NoListener,
+        );
+    }
+
+    Ok(Json(ValidateResponse {
+        legal: reason.is_none(),
+        reason,
+        snapshot: game.snapshot(),
+        state: game.game_state(),
+    }))
+}
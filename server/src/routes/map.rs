@@ -1,28 +1,259 @@
-use axum::{Router, extract::State, routing::get};
-use game::data::{Connection, Round, Station};
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+    routing::get,
+};
+use game::{
+    data::{Connection, Round, Station},
+    event::DetectiveTransportData,
+    map::render::{self, Overlay},
+    map_utils::{self, ReachableStation, RouteStep},
+};
+use serde::Deserialize;
+use uuid::Uuid;
 
-use crate::{AppState, services::data::DataServiceHandle};
+use crate::{
+    AppError, AppState,
+    services::{
+        data::{DEFAULT_MAP, DataServiceHandle, MapId, MapRegistryHandle},
+        game::{GameId, GameServiceHandle},
+        replay::{ReplayStoreError, ReplayStoreHandle},
+    },
+};
 
 use axum::Json;
 
 pub fn routes(state: AppState) -> Router {
     Router::new()
+        .route("/", get(get_all_maps))
         .route("/stations", get(get_all_stations))
         .route("/connections", get(get_all_connections))
         .route("/rounds", get(get_all_rounds))
+        .route("/svg", get(get_svg))
+        .route("/dot", get(get_dot))
+        .route("/route", get(get_route))
+        .route("/reachable", get(get_reachable))
         .with_state(state)
 }
 
-async fn get_all_stations(State(data_service): State<DataServiceHandle>) -> Json<Vec<Station>> {
-    Json(data_service.get_all_stations())
+#[derive(Deserialize)]
+struct MapQuery {
+    map: Option<MapId>,
+}
+
+impl MapQuery {
+    fn resolve(&self, maps: &MapRegistryHandle) -> Result<DataServiceHandle, AppError> {
+        maps.get(self.map.as_deref().unwrap_or(DEFAULT_MAP))
+            .ok_or_else(|| AppError::NotFound("map".to_string()))
+    }
+}
+
+/// the overlay comes from either a running game or a replay
+#[derive(Deserialize)]
+struct RenderQuery {
+    map: Option<MapId>,
+    game: Option<GameId>,
+    replay: Option<Uuid>,
+    /// number of replay actions to show, all by default
+    step: Option<usize>,
+}
+
+/// routes with the same number of moves beyond this are left out
+const MAX_ROUTES: usize = 10;
+
+/// missing tickets default to the ones a detective starts with
+#[derive(Deserialize)]
+struct RouteQuery {
+    map: Option<MapId>,
+    from: u8,
+    taxi: Option<u8>,
+    bus: Option<u8>,
+    underground: Option<u8>,
+}
+
+/// the end of a route, only `/map/route` needs it
+#[derive(Deserialize)]
+struct Destination {
+    to: u8,
+}
+
+impl RouteQuery {
+    fn tickets(&self) -> DetectiveTransportData {
+        let default = DetectiveTransportData::default();
+
+        DetectiveTransportData {
+            taxi: self.taxi.unwrap_or(default.taxi),
+            bus: self.bus.unwrap_or(default.bus),
+            underground: self.underground.unwrap_or(default.underground),
+        }
+    }
+
+    fn assert_station(&self, stations: &[Station], id: u8) -> Result<(), AppError> {
+        if stations.iter().any(|station| station.id == id) {
+            Ok(())
+        } else {
+            Err(AppError::NotFound(format!("station {id}")))
+        }
+    }
+}
+
+async fn get_all_maps(State(maps): State<MapRegistryHandle>) -> Json<Vec<MapId>> {
+    Json(maps.ids())
+}
+
+async fn get_all_stations(
+    State(maps): State<MapRegistryHandle>,
+    Query(query): Query<MapQuery>,
+) -> Result<Json<Vec<Station>>, AppError> {
+    Ok(Json(query.resolve(&maps)?.get_all_stations()))
 }
 
 async fn get_all_connections(
-    State(data_service): State<DataServiceHandle>,
-) -> Json<Vec<Connection>> {
-    Json(data_service.get_all_connections())
+    State(maps): State<MapRegistryHandle>,
+    Query(query): Query<MapQuery>,
+) -> Result<Json<Vec<Connection>>, AppError> {
+    Ok(Json(query.resolve(&maps)?.get_all_connections()))
+}
+
+async fn get_all_rounds(
+    State(maps): State<MapRegistryHandle>,
+    Query(query): Query<MapQuery>,
+) -> Result<Json<Vec<Round>>, AppError> {
+    Ok(Json(query.resolve(&maps)?.get_all_rounds()))
+}
+
+async fn get_svg(
+    State(maps): State<MapRegistryHandle>,
+    State(games): State<GameServiceHandle>,
+    State(replays): State<ReplayStoreHandle>,
+    Query(query): Query<RenderQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let (map, overlay) = resolve_render(&maps, &games, &replays, query).await?;
+    let svg = render::to_svg(
+        &map.get_all_stations(),
+        &map.get_all_connections(),
+        &overlay,
+    );
+
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+async fn get_dot(
+    State(maps): State<MapRegistryHandle>,
+    State(games): State<GameServiceHandle>,
+    State(replays): State<ReplayStoreHandle>,
+    Query(query): Query<RenderQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let (map, overlay) = resolve_render(&maps, &games, &replays, query).await?;
+    let dot = render::to_dot(
+        &map.get_all_stations(),
+        &map.get_all_connections(),
+        &overlay,
+    );
+
+    Ok(([(header::CONTENT_TYPE, "text/vnd.graphviz")], dot))
+}
+
+async fn get_route(
+    State(maps): State<MapRegistryHandle>,
+    Query(query): Query<RouteQuery>,
+    Query(Destination { to }): Query<Destination>,
+) -> Result<Json<Vec<Vec<RouteStep>>>, AppError> {
+    let map = MapQuery {
+        map: query.map.clone(),
+    }
+    .resolve(&maps)?;
+    let stations = map.get_all_stations();
+    query.assert_station(&stations, query.from)?;
+    query.assert_station(&stations, to)?;
+
+    Ok(Json(map_utils::shortest_routes(
+        &map.get_all_connections(),
+        query.from,
+        to,
+        &query.tickets(),
+        MAX_ROUTES,
+    )))
 }
 
-async fn get_all_rounds(State(data_service): State<DataServiceHandle>) -> Json<Vec<Round>> {
-    Json(data_service.get_all_rounds())
+async fn get_reachable(
+    State(maps): State<MapRegistryHandle>,
+    Query(query): Query<RouteQuery>,
+) -> Result<Json<Vec<ReachableStation>>, AppError> {
+    let map = MapQuery {
+        map: query.map.clone(),
+    }
+    .resolve(&maps)?;
+    query.assert_station(&map.get_all_stations(), query.from)?;
+
+    Ok(Json(map_utils::reachable_stations(
+        &map.get_all_connections(),
+        query.from,
+        &query.tickets(),
+    )))
+}
+
+/// the map defaults to the one the game or replay is played on
+async fn resolve_render(
+    maps: &MapRegistryHandle,
+    games: &GameServiceHandle,
+    replays: &ReplayStoreHandle,
+    query: RenderQuery,
+) -> Result<(DataServiceHandle, Overlay), AppError> {
+    let (overlay_map, overlay) = match (query.game, query.replay) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::InvalidQuery(
+                "only one of game and replay can be shown".to_string(),
+            ));
+        }
+        (Some(game_id), None) => {
+            let game = games
+                .lock()
+                .await
+                .get_game(&game_id)
+                .map_err(|_| AppError::NotFound("game".to_string()))?;
+            let (map, state) = game
+                .public_state()
+                .await
+                .map_err(|_| AppError::NotFound("game".to_string()))?;
+
+            (Some(map), Overlay::from_state(&state))
+        }
+        (None, Some(replay_id)) => {
+            let replays = replays.clone();
+            let replay = match tokio::task::spawn_blocking(move || replays.get(&replay_id))
+                .await
+                .map_err(AppError::replay_store)?
+            {
+                Ok(replay) => replay,
+                Err(ReplayStoreError::UnknownReplay) => {
+                    return Err(AppError::NotFound("replay".to_string()));
+                }
+                Err(err) => return Err(AppError::replay_store(err)),
+            };
+
+            (
+                Some(replay.map.clone().unwrap_or(DEFAULT_MAP.to_string())),
+                Overlay::from_replay(&replay, query.step),
+            )
+        }
+        (None, None) => (None, Overlay::default()),
+    };
+
+    if let (Some(map), Some(overlay_map)) = (&query.map, &overlay_map)
+        && map != overlay_map
+    {
+        return Err(AppError::InvalidQuery(format!(
+            "the overlay is on map {overlay_map}"
+        )));
+    }
+
+    let map = MapQuery {
+        map: query.map.or(overlay_map),
+    }
+    .resolve(maps)?;
+
+    Ok((map, overlay))
 }
@@ -1,6 +1,17 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use game::data::{Connection, Round, Station};
+use game::{
+    data::{Connection, Round, Station},
+    map::{MapError, MapFile},
+};
+use thiserror::Error;
+
+use crate::services::data::service::DataService;
 
 pub mod service;
 
@@ -10,7 +21,80 @@ pub trait DataServiceTrait: Send + Sync {
     fn get_all_stations(&self) -> Vec<Station>;
     fn get_all_connections(&self) -> Vec<Connection>;
     fn get_all_rounds(&self) -> Vec<Round>;
-    fn get_colors(&self) -> [&str; 5];
+    fn get_colors(&self) -> Vec<String>;
+    /// all stations detectives can start from
+    fn get_detective_stations(&self) -> Vec<u8>;
     fn get_random_detective_stations(&self, count: usize) -> Vec<u8>;
     fn get_random_mister_x_station(&self) -> u8;
 }
+
+pub type MapId = String;
+
+/// map used when a lobby doesn't choose one
+pub const DEFAULT_MAP: &str = "london";
+
+pub type MapRegistryHandle = Arc<MapRegistry>;
+
+#[derive(Error, Debug)]
+pub enum MapLoadError {
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("{0}: {1}")]
+    Invalid(PathBuf, MapError),
+}
+
+/// all maps lobbies can be played on
+pub struct MapRegistry {
+    maps: HashMap<MapId, DataServiceHandle>,
+}
+
+impl MapRegistry {
+    pub fn new(default_map: DataServiceHandle) -> Self {
+        Self {
+            maps: HashMap::from([(DEFAULT_MAP.to_string(), default_map)]),
+        }
+    }
+
+    pub fn insert(&mut self, id: impl Into<MapId>, map: DataServiceHandle) {
+        self.maps.insert(id.into(), map);
+    }
+
+    /// loads every `.toml` file as a map named after the file, a missing directory is ignored
+    pub fn load_dir(&mut self, dir: &Path) -> Result<(), MapLoadError> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(MapLoadError::Io(dir.to_path_buf(), err)),
+        };
+
+        for entry in entries {
+            let path = entry
+                .map_err(|err| MapLoadError::Io(dir.to_path_buf(), err))?
+                .path();
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let content =
+                fs::read_to_string(&path).map_err(|err| MapLoadError::Io(path.clone(), err))?;
+            let map =
+                MapFile::parse(&content).map_err(|err| MapLoadError::Invalid(path.clone(), err))?;
+            self.insert(id, Arc::new(DataService::new(map)));
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<DataServiceHandle> {
+        self.maps.get(id).cloned()
+    }
+
+    pub fn ids(&self) -> Vec<MapId> {
+        let mut ids: Vec<_> = self.maps.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+}
@@ -1,29 +1,41 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::{self, Arc},
+};
 
 use game::{
-    Game, GameError,
-    event::{EventListener, GameState, Role},
+    Game, GameError, Snapshot,
+    event::{
+        DetectiveActionType, EventListener, GameState, GameStateDelta, LegalMoves,
+        MisterXActionType, Role,
+    },
     replay::Replay,
 };
-use packets::{GameEndedPacket, GameStartedPacket, ServerPacket, StartMovePacket};
+use packets::{
+    ErrorCode, GameEndedPacket, GameStartedPacket, ServerPacket, SpectatingPacket, SpectatorView,
+    StartMovePacket, ToErrorCode,
+};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::{fs, sync::Mutex};
+use tokio::sync::{
+    Mutex,
+    mpsc::{self, Sender},
+    oneshot,
+};
 use uuid::Uuid;
 
-use crate::{
-    SettingsHandle,
-    services::{
-        data::DataServiceHandle,
-        lobby::{Lobby, LobbyId, Player},
-        ws_connection::WsConnectionServiceHandle,
-    },
+use crate::services::{
+    data::{MapId, MapRegistryHandle},
+    lobby::{BotSeat, Lobby, LobbyId, LobbyServiceError, Player, PlayerId},
+    persistence::PersistenceServiceHandle,
+    replay::ReplayStoreHandle,
 };
 
 pub type GameId = Uuid;
 
 pub type GameServiceHandle = Arc<Mutex<GameService>>;
-pub type GameHandle = Arc<Mutex<Game<GameEventListener>>>;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum GameServiceError {
@@ -32,23 +44,104 @@ pub enum GameServiceError {
 
     #[error("unknown game")]
     UnknownGame,
-    #[error("game does not have enough players")]
-    NotEnoughPlayers,
+    #[error("not your turn")]
+    NotAllowedForUser,
+    #[error("not your role")]
+    WrongRole,
+    #[error("omniscient spectators are disabled")]
+    OmniscientSpectatorsDisabled,
+    #[error("game has too many spectators")]
+    TooManySpectators,
+    /// the reason is only logged, as it may contain details of the storage backend
+    #[error("failed to save the replay")]
+    ReplayNotSaved,
+}
+
+impl ToErrorCode for GameServiceError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            GameServiceError::Game(err) => err.code(),
+            GameServiceError::UnknownGame => ErrorCode::UnknownGame,
+            GameServiceError::NotAllowedForUser => ErrorCode::NotYourTurn,
+            GameServiceError::WrongRole => ErrorCode::WrongRole,
+            GameServiceError::OmniscientSpectatorsDisabled => {
+                ErrorCode::OmniscientSpectatorsDisabled
+            }
+            GameServiceError::TooManySpectators => ErrorCode::TooManySpectators,
+            GameServiceError::ReplayNotSaved => ErrorCode::Internal,
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            GameServiceError::Game(err) => err.details(),
+            _ => None,
+        }
+    }
+}
+
+pub struct Spectator {
+    pub uuid: Uuid,
+    pub ws_sender: Sender<ServerPacket>,
+    pub view: SpectatorView,
 }
 
 pub struct GameEventListener {
-    settings: SettingsHandle,
+    replays: ReplayStoreHandle,
     game_id: Uuid,
     detective_players: Vec<Player>,
     mister_x_player: Player,
+    /// lobby the game was started from, used to set up a rematch
+    lobby: Lobby,
+    mister_x_index: usize,
+    spectators: Vec<Spectator>,
+    max_spectators: usize,
+    /// station of Mister X at the end of each round, used for the delayed omniscient view
+    mister_x_stations: sync::Mutex<Vec<u8>>,
+}
+
+/// everything needed to continue a game after a restart
+#[derive(Serialize, Deserialize)]
+struct StoredGame {
+    lobby: Lobby,
+    mister_x_index: usize,
+    mister_x_stations: Vec<u8>,
+    snapshot: Snapshot,
 }
 
 impl GameEventListener {
-    pub fn all_players(&self) -> Vec<Uuid> {
-        let mut players = vec![];
-        players.extend(self.detective_players.iter().map(|player| player.uuid));
-        players.push(self.mister_x_player.uuid);
-        players
+    fn new(
+        replays: ReplayStoreHandle,
+        game_id: GameId,
+        lobby: Lobby,
+        mister_x_index: usize,
+        max_spectators: usize,
+    ) -> Self {
+        let detective_players = lobby
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != mister_x_index)
+            .map(|(_, player)| player.clone())
+            .collect();
+
+        Self {
+            replays,
+            game_id,
+            detective_players,
+            mister_x_player: lobby.players[mister_x_index].clone(),
+            lobby,
+            mister_x_index,
+            spectators: vec![],
+            max_spectators,
+            mister_x_stations: sync::Mutex::new(vec![]),
+        }
+    }
+
+    fn players(&self) -> impl Iterator<Item = &Player> {
+        self.detective_players
+            .iter()
+            .chain(std::iter::once(&self.mister_x_player))
     }
 
     pub fn get_user_role(&self, id: Uuid) -> Role {
@@ -59,38 +152,188 @@ impl GameEventListener {
         }
     }
 
+    /// sends the full state to a player who missed a delta
+    async fn resync(&self, player_id: PlayerId, mut state: GameState, show_mister_x: bool) {
+        let Some(player) = self.players().find(|player| player.uuid == player_id) else {
+            return;
+        };
+
+        if self.get_user_role(player_id) == Role::Detective && !show_mister_x {
+            state.mister_x.station_id = None;
+        }
+
+        let _ = player.ws_sender.send(ServerPacket::GameState(state)).await;
+    }
+
+    /// lobby with the same players to set up a rematch, Mister X rotates to the next player
+    fn rematch_lobby(&self, winner: Option<&Role>) -> Lobby {
+        let mut lobby = self.lobby.clone();
+
+        match winner {
+            Some(Role::MisterX) => {
+                *lobby.scores.entry(self.mister_x_player.uuid).or_default() += 1;
+            }
+            Some(Role::Detective) => {
+                for player in &self.detective_players {
+                    *lobby.scores.entry(player.uuid).or_default() += 1;
+                }
+            }
+            None => {}
+        }
+
+        lobby.mister_x = Some((self.mister_x_index + 1) % lobby.players.len());
+        lobby.rematch = Some(HashSet::new());
+
+        lobby
+    }
+
+    /// replaces the connection of a player and sends everything needed to continue playing
+    async fn resume(
+        &mut self,
+        player_id: PlayerId,
+        ws_sender: Sender<ServerPacket>,
+        active_role: &Role,
+        legal_moves: LegalMoves,
+        mut state: GameState,
+        show_mister_x: bool,
+    ) {
+        let Some(player) = self
+            .detective_players
+            .iter_mut()
+            .chain(std::iter::once(&mut self.mister_x_player))
+            .find(|player| player.uuid == player_id)
+        else {
+            return;
+        };
+        player.ws_sender = ws_sender.clone();
+        let session = player.session;
+        let player_wants_legal_moves = player.options.legal_moves;
+
+        let role = self.get_user_role(player_id);
+        let send_legal_moves = player_wants_legal_moves && role == *active_role;
+        if role == Role::Detective && !show_mister_x {
+            state.mister_x.station_id = None;
+        }
+
+        let _ = ws_sender
+            .send(ServerPacket::GameStarted(GameStartedPacket {
+                role,
+                session,
+            }))
+            .await;
+        let _ = ws_sender
+            .send(ServerPacket::StartMove(StartMovePacket {
+                role: active_role.clone(),
+            }))
+            .await;
+        if send_legal_moves {
+            let _ = ws_sender.send(ServerPacket::LegalMoves(legal_moves)).await;
+        }
+        let _ = ws_sender.send(ServerPacket::GameState(state)).await;
+    }
+
+    pub async fn add_spectator(
+        &mut self,
+        spectator: Spectator,
+        state: GameState,
+        show_mister_x: bool,
+    ) -> Result<(), GameServiceError> {
+        if spectator.view == SpectatorView::Omniscient
+            && !self.lobby.settings.spectators.allow_omniscient
+        {
+            return Err(GameServiceError::OmniscientSpectatorsDisabled);
+        }
+
+        self.spectators
+            .retain(|spectator| !spectator.ws_sender.is_closed());
+        if self.spectators.len() >= self.max_spectators {
+            return Err(GameServiceError::TooManySpectators);
+        }
+
+        let _ = spectator
+            .ws_sender
+            .send(ServerPacket::Spectating(SpectatingPacket {
+                view: spectator.view.clone(),
+            }))
+            .await;
+        let _ = spectator
+            .ws_sender
+            .send(ServerPacket::GameState(self.spectator_state(
+                &spectator.view,
+                state,
+                show_mister_x,
+            )))
+            .await;
+
+        self.spectators.push(spectator);
+
+        Ok(())
+    }
+
+    fn spectator_state(
+        &self,
+        view: &SpectatorView,
+        mut state: GameState,
+        show_mister_x: bool,
+    ) -> GameState {
+        if show_mister_x {
+            return state;
+        }
+
+        state.mister_x.station_id = match view {
+            SpectatorView::Detective => None,
+            SpectatorView::Omniscient => state
+                .round
+                .checked_sub(self.lobby.settings.spectators.omniscient_delay)
+                .and_then(|round| {
+                    self.mister_x_stations
+                        .lock()
+                        .unwrap()
+                        .get(round as usize)
+                        .copied()
+                }),
+        };
+
+        state
+    }
+
     async fn send_all(&self, packet: ServerPacket) {
         for player in &self.detective_players {
-            player.ws_sender.send(packet.clone()).await.unwrap();
+            let _ = player.ws_sender.send(packet.clone()).await;
         }
-        self.mister_x_player.ws_sender.send(packet).await.unwrap();
+        for spectator in &self.spectators {
+            let _ = spectator.ws_sender.send(packet.clone()).await;
+        }
+        let _ = self.mister_x_player.ws_sender.send(packet).await;
     }
 }
 
 impl EventListener for GameEventListener {
     async fn on_game_start(&self) {
-        self.mister_x_player
-            .ws_sender
-            .send(ServerPacket::GameStarted(GameStartedPacket {
-                role: Role::MisterX,
-            }))
-            .await
-            .unwrap();
-
-        for player in &self.detective_players {
-            player
+        for player in self.players() {
+            let _ = player
                 .ws_sender
                 .send(ServerPacket::GameStarted(GameStartedPacket {
-                    role: Role::Detective,
+                    role: self.get_user_role(player.uuid),
+                    session: player.session,
                 }))
-                .await
-                .unwrap();
+                .await;
         }
     }
 
-    async fn on_start_round(&self, role: &Role) {
+    async fn on_start_round(&self, role: &Role, legal_moves: &LegalMoves) {
         let packet = StartMovePacket { role: role.clone() };
         self.send_all(ServerPacket::StartMove(packet.clone())).await;
+
+        for player in self
+            .players()
+            .filter(|player| player.options.legal_moves && self.get_user_role(player.uuid) == *role)
+        {
+            let _ = player
+                .ws_sender
+                .send(ServerPacket::LegalMoves(legal_moves.clone()))
+                .await;
+        }
     }
 
     async fn on_end_move(&self) {
@@ -98,15 +341,20 @@ impl EventListener for GameEventListener {
     }
 
     async fn on_game_ended(&self, replay: &Replay) {
-        fs::create_dir_all(&self.settings.replay_dir).await.unwrap();
-        fs::write(
-            self.settings
-                .replay_dir
-                .join(format!("{}.json", self.game_id)),
-            serde_json::to_string_pretty(replay).unwrap(),
-        )
-        .await
-        .unwrap();
+        let replays = self.replays.clone();
+        let game_id = self.game_id;
+        let stored_replay = Replay {
+            map: Some(self.lobby.settings.map.clone()),
+            ..replay.clone()
+        };
+        let saved =
+            tokio::task::spawn_blocking(move || replays.save(game_id, &stored_replay)).await;
+
+        if let Ok(Err(err)) = saved {
+            tracing::error!("failed to save replay of game {}: {err}", self.game_id);
+            self.send_all(ServerPacket::from_error(GameServiceError::ReplayNotSaved))
+                .await;
+        }
 
         self.send_all(ServerPacket::GameEnded(GameEndedPacket {
             winner: replay.winner.clone(),
@@ -114,110 +362,474 @@ impl EventListener for GameEventListener {
         .await;
     }
 
-    async fn on_game_state_update(&self, mut state: GameState, show_mister_x: bool) {
-        self.mister_x_player
+    async fn on_game_state_update(
+        &self,
+        mut state: GameState,
+        delta: Option<&GameStateDelta>,
+        show_mister_x: bool,
+    ) {
+        if let Some(station_id) = state.mister_x.station_id {
+            let mut stations = self.mister_x_stations.lock().unwrap();
+            stations.truncate(state.round as usize);
+            stations.push(station_id);
+        }
+
+        let _ = self
+            .mister_x_player
             .ws_sender
-            .send(ServerPacket::GameState(state.clone()))
-            .await
-            .unwrap();
+            .send(state_packet(&self.mister_x_player, &state, delta))
+            .await;
+
+        for spectator in &self.spectators {
+            let _ = spectator
+                .ws_sender
+                .send(ServerPacket::GameState(self.spectator_state(
+                    &spectator.view,
+                    state.clone(),
+                    show_mister_x,
+                )))
+                .await;
+        }
 
         if !show_mister_x {
             state.mister_x.station_id = None;
         }
 
         for player in &self.detective_players {
-            player
+            let _ = player
                 .ws_sender
-                .send(ServerPacket::GameState(state.clone()))
-                .await
-                .unwrap();
+                .send(state_packet(player, &state, delta))
+                .await;
+        }
+    }
+}
+
+/// deltas only go to players who asked for them
+fn state_packet(
+    player: &Player,
+    state: &GameState,
+    delta: Option<&GameStateDelta>,
+) -> ServerPacket {
+    match delta {
+        Some(delta) if player.options.state_deltas => ServerPacket::GameStateDelta(delta.clone()),
+        _ => ServerPacket::GameState(state.clone()),
+    }
+}
+
+pub enum GameCommand {
+    Start,
+    Spectate {
+        spectator: Spectator,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+    MoveMisterX {
+        player: PlayerId,
+        moves: Vec<(u8, MisterXActionType)>,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+    MoveDetective {
+        player: PlayerId,
+        color: String,
+        station_id: u8,
+        transport_type: DetectiveActionType,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+    SubmitMove {
+        player: PlayerId,
+        reply: oneshot::Sender<Result<Option<Lobby>, GameServiceError>>,
+    },
+    Resume {
+        player: PlayerId,
+        ws_sender: Sender<ServerPacket>,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+    PublicState {
+        reply: oneshot::Sender<Result<(MapId, GameState), GameServiceError>>,
+    },
+    Resync {
+        player: PlayerId,
+        reply: oneshot::Sender<Result<(), GameServiceError>>,
+    },
+}
+
+/// handle to a game running in its own task, cheap to clone
+#[derive(Clone)]
+pub struct GameHandle {
+    commands: mpsc::Sender<GameCommand>,
+}
+
+impl GameHandle {
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<Result<T, GameServiceError>>) -> GameCommand,
+    ) -> Result<T, GameServiceError> {
+        let (reply, response) = oneshot::channel();
+
+        self.commands
+            .send(command(reply))
+            .await
+            .map_err(|_| GameServiceError::UnknownGame)?;
+
+        response.await.map_err(|_| GameServiceError::UnknownGame)?
+    }
+
+    pub async fn start(&self) -> Result<(), GameServiceError> {
+        self.commands
+            .send(GameCommand::Start)
+            .await
+            .map_err(|_| GameServiceError::UnknownGame)
+    }
+
+    pub async fn spectate(&self, spectator: Spectator) -> Result<(), GameServiceError> {
+        self.request(|reply| GameCommand::Spectate { spectator, reply })
+            .await
+    }
+
+    pub async fn move_mister_x(
+        &self,
+        player: PlayerId,
+        moves: Vec<(u8, MisterXActionType)>,
+    ) -> Result<(), GameServiceError> {
+        self.request(|reply| GameCommand::MoveMisterX {
+            player,
+            moves,
+            reply,
+        })
+        .await
+    }
+
+    pub async fn move_detective(
+        &self,
+        player: PlayerId,
+        color: String,
+        station_id: u8,
+        transport_type: DetectiveActionType,
+    ) -> Result<(), GameServiceError> {
+        self.request(|reply| GameCommand::MoveDetective {
+            player,
+            color,
+            station_id,
+            transport_type,
+            reply,
+        })
+        .await
+    }
+
+    /// returns the lobby for a rematch if the game ended with this move
+    pub async fn submit_move(&self, player: PlayerId) -> Result<Option<Lobby>, GameServiceError> {
+        self.request(|reply| GameCommand::SubmitMove { player, reply })
+            .await
+    }
+
+    pub async fn resume(
+        &self,
+        player: PlayerId,
+        ws_sender: Sender<ServerPacket>,
+    ) -> Result<(), GameServiceError> {
+        self.request(|reply| GameCommand::Resume {
+            player,
+            ws_sender,
+            reply,
+        })
+        .await
+    }
+
+    pub async fn resync(&self, player: PlayerId) -> Result<(), GameServiceError> {
+        self.request(|reply| GameCommand::Resync { player, reply })
+            .await
+    }
+
+    /// map and state of the game as the detectives see it
+    pub async fn public_state(&self) -> Result<(MapId, GameState), GameServiceError> {
+        self.request(|reply| GameCommand::PublicState { reply })
+            .await
+    }
+}
+
+/// owns the game and handles its commands one after another until the game ended
+async fn run_game(
+    mut game: Game<GameEventListener>,
+    mut commands: mpsc::Receiver<GameCommand>,
+    persistence: PersistenceServiceHandle,
+) {
+    while let Some(command) = commands.recv().await {
+        match command {
+            GameCommand::Start => {
+                game.start().await;
+                save_game(&game, &persistence);
+            }
+            GameCommand::Spectate { spectator, reply } => {
+                let state = game.game_state();
+                let show_mister_x = game.should_show_mister_x();
+
+                let result = game
+                    .event_listener_mut()
+                    .add_spectator(spectator, state, show_mister_x)
+                    .await;
+                let _ = reply.send(result);
+            }
+            GameCommand::MoveMisterX {
+                player,
+                moves,
+                reply,
+            } => {
+                let result = assert_own_round(&game, &player, Role::MisterX)
+                    .and_then(|_| game.move_mister_x(moves).map_err(Into::into));
+                let _ = reply.send(result);
+            }
+            GameCommand::MoveDetective {
+                player,
+                color,
+                station_id,
+                transport_type,
+                reply,
+            } => {
+                let result = match assert_own_round(&game, &player, Role::Detective) {
+                    Ok(()) => game
+                        .move_detective(color, station_id, transport_type)
+                        .await
+                        .map_err(Into::into),
+                    Err(err) => Err(err),
+                };
+                let _ = reply.send(result);
+            }
+            GameCommand::SubmitMove { player, reply } => {
+                let role = game.event_listener().get_user_role(player);
+                let result = match assert_own_round(&game, &player, role) {
+                    Ok(()) => game.end_move().await.map_err(Into::into),
+                    Err(err) => Err(err),
+                };
+
+                match result {
+                    Ok(true) => {
+                        persistence.remove_game(&game.event_listener().game_id);
+
+                        let lobby = game.event_listener().rematch_lobby(game.winner());
+                        let _ = reply.send(Ok(Some(lobby)));
+                        return;
+                    }
+                    Ok(false) => {
+                        save_game(&game, &persistence);
+                        let _ = reply.send(Ok(None));
+                    }
+                    Err(err) => {
+                        let _ = reply.send(Err(err));
+                    }
+                }
+            }
+            GameCommand::Resume {
+                player,
+                ws_sender,
+                reply,
+            } => {
+                let active_role = game.active_role().clone();
+                let state = game.game_state();
+                let show_mister_x = game.should_show_mister_x();
+
+                let legal_moves = game.legal_moves();
+
+                game.event_listener_mut()
+                    .resume(
+                        player,
+                        ws_sender,
+                        &active_role,
+                        legal_moves,
+                        state,
+                        show_mister_x,
+                    )
+                    .await;
+                let _ = reply.send(Ok(()));
+            }
+            GameCommand::PublicState { reply } => {
+                let mut state = game.game_state();
+                if !game.should_show_mister_x() {
+                    state.mister_x.station_id = None;
+                }
+
+                let map = game.event_listener().lobby.settings.map.clone();
+                let _ = reply.send(Ok((map, state)));
+            }
+            GameCommand::Resync { player, reply } => {
+                game.event_listener()
+                    .resync(player, game.game_state(), game.should_show_mister_x())
+                    .await;
+                let _ = reply.send(Ok(()));
+            }
         }
     }
 }
 
+fn save_game(game: &Game<GameEventListener>, persistence: &PersistenceServiceHandle) {
+    let listener = game.event_listener();
+
+    let stored = StoredGame {
+        lobby: listener.lobby.clone(),
+        mister_x_index: listener.mister_x_index,
+        mister_x_stations: listener.mister_x_stations.lock().unwrap().clone(),
+        snapshot: game.snapshot(),
+    };
+
+    persistence.save_game(&listener.game_id, &stored);
+}
+
+fn assert_own_round(
+    game: &Game<GameEventListener>,
+    player: &PlayerId,
+    role: Role,
+) -> Result<(), GameServiceError> {
+    if game.event_listener().get_user_role(*player) != role {
+        return Err(GameServiceError::WrongRole);
+    }
+    if game.active_role() != &role {
+        return Err(GameServiceError::NotAllowedForUser);
+    }
+
+    Ok(())
+}
+
+/// game and player a session belongs to
+#[derive(Clone, Copy)]
+pub struct Session {
+    pub game_id: GameId,
+    pub player_id: PlayerId,
+}
+
 pub struct GameService {
     games: HashMap<GameId, GameHandle>,
-    data_service: DataServiceHandle,
-    ws_connection_service: WsConnectionServiceHandle,
+    sessions: HashMap<Uuid, Session>,
+    maps: MapRegistryHandle,
+    persistence: PersistenceServiceHandle,
+    replays: ReplayStoreHandle,
+    max_spectators: usize,
+    /// bots of the restored games, started once the services are running
+    restored_bots: Vec<BotSeat>,
 }
 
 impl GameService {
+    /// restores and continues the games stored by a previous run
     pub fn new(
-        data_service: DataServiceHandle,
-        ws_connection_service: WsConnectionServiceHandle,
+        maps: MapRegistryHandle,
+        persistence: PersistenceServiceHandle,
+        replays: ReplayStoreHandle,
+        max_spectators: usize,
     ) -> Self {
-        Self {
+        let mut service = Self {
             games: HashMap::new(),
-            data_service,
-            ws_connection_service,
+            sessions: HashMap::new(),
+            maps,
+            persistence,
+            replays,
+            max_spectators,
+            restored_bots: vec![],
+        };
+
+        for (game_id, stored) in service.persistence.load_games::<StoredGame>() {
+            let Some(map) = service.maps.get(&stored.lobby.settings.map) else {
+                tracing::warn!(
+                    "can't restore game {game_id}, map {} is missing",
+                    stored.lobby.settings.map
+                );
+                continue;
+            };
+            service
+                .restored_bots
+                .extend(stored.lobby.bot_seats(&game_id));
+
+            let mut event_listener = GameEventListener::new(
+                service.replays.clone(),
+                game_id,
+                stored.lobby,
+                stored.mister_x_index,
+                service.max_spectators,
+            );
+            event_listener.mister_x_stations = sync::Mutex::new(stored.mister_x_stations);
+
+            let game = Game::restore(
+                stored.snapshot,
+                map.get_all_connections(),
+                map.get_all_rounds(),
+                event_listener,
+            );
+            service.spawn(game_id, game);
         }
+
+        service
     }
 
+    /// spawns a task running the game, the lobby has to be validated beforehand
     pub fn add_game_from_lobby(
         &mut self,
         lobby: &Lobby,
         lobby_id: &LobbyId,
-        settings: SettingsHandle,
-    ) -> Result<(), GameServiceError> {
-        if lobby.players.len() < 2 {
-            return Err(GameServiceError::NotEnoughPlayers);
-        }
-
-        let mut rng = rand::rng();
-
-        let mister_x = rng.random_range(0..lobby.players.len());
-
-        let colors = self.data_service.get_colors();
-        let detective_starts = self
-            .data_service
-            .get_random_detective_stations(lobby.settings.number_of_detectives);
-
-        let detective_players = lobby
-            .players
-            .iter()
-            .enumerate()
-            .filter(|(index, _)| *index != mister_x)
-            .map(|(_, player)| player.clone())
-            .collect();
-
-        let event_list = GameEventListener {
-            settings,
-            game_id: *lobby_id,
-            detective_players,
-            mister_x_player: lobby.players[mister_x].clone(),
-        };
+    ) -> Result<GameHandle, LobbyServiceError> {
+        let mister_x = lobby
+            .mister_x
+            .unwrap_or_else(|| rand::rng().random_range(0..lobby.players.len()));
+
+        let map = self
+            .maps
+            .get(&lobby.settings.map)
+            .ok_or(LobbyServiceError::UnknownMap)?;
+
+        let colors = map.get_colors();
+        let detective_starts =
+            map.get_random_detective_stations(lobby.settings.number_of_detectives);
+
+        let event_list = GameEventListener::new(
+            self.replays.clone(),
+            *lobby_id,
+            lobby.clone(),
+            mister_x,
+            self.max_spectators,
+        );
 
         let detectives_data = (0..lobby.settings.number_of_detectives)
-            .map(|i| (colors[i].to_string(), detective_starts[i]))
+            .map(|i| (colors[i].clone(), detective_starts[i]))
             .collect();
 
         let game = Game::new(
             detectives_data,
-            self.data_service.get_random_mister_x_station(),
-            self.data_service.get_all_connections(),
-            self.data_service.get_all_rounds(),
+            map.get_random_mister_x_station(),
+            map.get_all_connections(),
+            map.get_all_rounds(),
             event_list,
         );
 
-        self.games.insert(*lobby_id, Arc::new(Mutex::new(game)));
-
-        Ok(())
+        Ok(self.spawn(*lobby_id, game))
     }
 
-    pub async fn remove_game(&mut self, game_id: &GameId) {
-        for player in self
-            .get_game(game_id)
-            .unwrap()
-            .lock()
-            .await
-            .event_listener()
-            .all_players()
-        {
-            let mut connections = self.ws_connection_service.lock().await;
-            let _ = connections.set_game_id(player, None);
+    fn spawn(&mut self, game_id: GameId, game: Game<GameEventListener>) -> GameHandle {
+        for player in game.event_listener().players() {
+            self.sessions.insert(
+                player.session,
+                Session {
+                    game_id,
+                    player_id: player.uuid,
+                },
+            );
         }
 
+        let (commands, receiver) = mpsc::channel(16);
+        tokio::spawn(run_game(game, receiver, self.persistence.clone()));
+
+        let handle = GameHandle { commands };
+        self.games.insert(game_id, handle.clone());
+
+        handle
+    }
+
+    pub fn remove_game(&mut self, game_id: &GameId) {
         self.games.remove(game_id);
+        self.sessions
+            .retain(|_, session| session.game_id != *game_id);
+    }
+
+    pub fn take_restored_bots(&mut self) -> Vec<BotSeat> {
+        mem::take(&mut self.restored_bots)
+    }
+
+    pub fn find_session(&self, session: &Uuid) -> Option<Session> {
+        self.sessions.get(session).copied()
     }
 
     pub fn get_game(&self, game_id: &GameId) -> Result<GameHandle, GameServiceError> {
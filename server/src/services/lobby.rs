@@ -1,24 +1,150 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use thiserror::Error;
-use tokio::sync::{Mutex, mpsc::Sender};
+use tokio::sync::{
+    Mutex,
+    mpsc::{self, Sender},
+};
 use uuid::Uuid;
 
-use packets::ServerPacket;
+use packets::{
+    ErrorCode, LobbyPlayerData, LobbyReference, LobbyStatePacket, ServerPacket, SpectatorSettings,
+    ToErrorCode,
+};
 
+use crate::services::{
+    data::{DEFAULT_MAP, MapId, MapRegistryHandle},
+    persistence::PersistenceServiceHandle,
+};
+
+const CODE_LENGTH: usize = 6;
+const CODE_CHARACTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub number_of_detectives: usize,
+    pub spectators: SpectatorSettings,
+    pub public: bool,
+    #[serde(default = "default_map")]
+    pub map: MapId,
+}
+
+fn default_map() -> MapId {
+    DEFAULT_MAP.to_string()
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     pub uuid: Uuid,
+    /// secret the player can resume with after reconnecting
+    pub session: Uuid,
+    /// players restored from disk stay disconnected until they resume
+    #[serde(skip, default = "disconnected")]
     pub ws_sender: Sender<ServerPacket>,
+    #[serde(flatten)]
+    pub options: PlayerOptions,
+    /// name of the bot playing the seat
+    #[serde(default)]
+    pub bot: Option<String>,
+}
+
+/// a seat of a stored lobby or game whose bot has to be started again after a restart
+pub struct BotSeat {
+    pub lobby_id: LobbyId,
+    pub player_id: PlayerId,
+    pub name: String,
+    pub map: MapId,
 }
 
+/// what a player asked for when joining
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerOptions {
+    /// whether the player receives the legal moves at the start of each own turn
+    pub legal_moves: bool,
+    /// whether tentative moves are sent as deltas instead of full states
+    pub state_deltas: bool,
+}
+
+fn disconnected() -> Sender<ServerPacket> {
+    mpsc::channel(1).0
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Lobby {
+    /// short code players can join with instead of the id
+    pub code: String,
     pub settings: Settings,
     pub players: Vec<Player>,
+    /// index of the player playing Mister X, chosen randomly if not set
+    pub mister_x: Option<usize>,
+    pub scores: HashMap<PlayerId, u32>,
+    /// players which accepted a rematch, only set for lobbies created from a finished game
+    pub rematch: Option<HashSet<PlayerId>>,
+}
+
+impl Lobby {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            code: String::new(),
+            settings,
+            players: vec![],
+            mister_x: None,
+            scores: HashMap::new(),
+            rematch: None,
+        }
+    }
+
+    pub fn bot_seats(&self, lobby_id: &LobbyId) -> impl Iterator<Item = BotSeat> {
+        self.players.iter().filter_map(|player| {
+            Some(BotSeat {
+                lobby_id: *lobby_id,
+                player_id: player.uuid,
+                name: player.bot.clone()?,
+                map: self.settings.map.clone(),
+            })
+        })
+    }
+
+    /// one player plays Mister X, every other player needs at least one detective
+    pub fn is_full(&self) -> bool {
+        self.players.len() > self.settings.number_of_detectives
+    }
+
+    pub fn state(&self, lobby_id: &LobbyId) -> LobbyStatePacket {
+        LobbyStatePacket {
+            id: *lobby_id,
+            players: self
+                .players
+                .iter()
+                .map(|player| LobbyPlayerData {
+                    id: player.uuid,
+                    score: self.scores.get(&player.uuid).copied().unwrap_or(0),
+                    accepted_rematch: self
+                        .rematch
+                        .as_ref()
+                        .is_some_and(|accepted| accepted.contains(&player.uuid)),
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn send_state(&self, lobby_id: &LobbyId) {
+        let state = self.state(lobby_id);
+
+        for player in &self.players {
+            let _ = player
+                .ws_sender
+                .send(ServerPacket::LobbyState(state.clone()))
+                .await;
+        }
+    }
 }
 
 pub type LobbyId = Uuid;
@@ -26,34 +152,174 @@ pub type PlayerId = Uuid;
 
 pub type LobbyServiceHandle = Arc<Mutex<LobbyService>>;
 
+#[derive(Serialize)]
+pub struct LobbyInfo {
+    pub id: LobbyId,
+    pub code: String,
+    pub players: usize,
+    pub settings: Settings,
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum LobbyServiceError {
     #[error("unknown lobby")]
     UnknownLobby,
+    #[error("lobby is reserved for a rematch")]
+    ReservedForRematch,
+    #[error("lobby is full")]
+    LobbyFull,
+    #[error("invalid number of detectives, must be between 1 and {0}")]
+    InvalidNumberOfDetectives(usize),
+    #[error("game does not have enough players")]
+    NotEnoughPlayers,
+    #[error("too many open lobbies")]
+    TooManyLobbies,
+    #[error("unknown map")]
+    UnknownMap,
+    #[error("no rematch available")]
+    NoRematch,
+}
+
+impl ToErrorCode for LobbyServiceError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            LobbyServiceError::UnknownLobby => ErrorCode::UnknownLobby,
+            LobbyServiceError::ReservedForRematch => ErrorCode::ReservedForRematch,
+            LobbyServiceError::LobbyFull => ErrorCode::LobbyFull,
+            LobbyServiceError::InvalidNumberOfDetectives(_) => ErrorCode::InvalidNumberOfDetectives,
+            LobbyServiceError::NotEnoughPlayers => ErrorCode::NotEnoughPlayers,
+            LobbyServiceError::TooManyLobbies => ErrorCode::TooManyLobbies,
+            LobbyServiceError::UnknownMap => ErrorCode::UnknownMap,
+            LobbyServiceError::NoRematch => ErrorCode::NoRematch,
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            LobbyServiceError::InvalidNumberOfDetectives(max) => Some(json!({ "max": max })),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Default)]
 pub struct LobbyService {
     lobbies: HashMap<LobbyId, Lobby>,
+    codes: HashMap<String, LobbyId>,
+    maps: MapRegistryHandle,
+    persistence: PersistenceServiceHandle,
+    max_lobbies: usize,
 }
 
 impl LobbyService {
-    pub fn create(&mut self, number_of_detectives: usize) -> LobbyId {
+    /// restores the lobbies stored by a previous run
+    pub fn new(
+        maps: MapRegistryHandle,
+        persistence: PersistenceServiceHandle,
+        max_lobbies: usize,
+    ) -> Self {
+        let lobbies: HashMap<LobbyId, Lobby> = persistence
+            .load_lobbies()
+            .into_iter()
+            .filter(|(id, lobby): &(LobbyId, Lobby)| {
+                let available = maps.get(&lobby.settings.map).is_some();
+                if !available {
+                    tracing::warn!(
+                        "can't restore lobby {id}, map {} is missing",
+                        lobby.settings.map
+                    );
+                }
+                available
+            })
+            .collect();
+        let codes = lobbies
+            .iter()
+            .map(|(id, lobby)| (lobby.code.clone(), *id))
+            .collect();
+
+        Self {
+            lobbies,
+            codes,
+            maps,
+            persistence,
+            max_lobbies,
+        }
+    }
+
+    pub fn create(&mut self, settings: Settings) -> Result<LobbyId, LobbyServiceError> {
+        let max_detectives = self.max_detectives(&settings.map)?;
+        if !(1..=max_detectives).contains(&settings.number_of_detectives) {
+            return Err(LobbyServiceError::InvalidNumberOfDetectives(max_detectives));
+        }
+        if self.lobbies.len() >= self.max_lobbies {
+            return Err(LobbyServiceError::TooManyLobbies);
+        }
+
+        Ok(self.add_lobby(Lobby::new(settings)))
+    }
+
+    /// every detective needs its own color and start station
+    fn max_detectives(&self, map: &str) -> Result<usize, LobbyServiceError> {
+        let map = self.maps.get(map).ok_or(LobbyServiceError::UnknownMap)?;
+
+        Ok(map
+            .get_colors()
+            .len()
+            .min(map.get_detective_stations().len()))
+    }
+
+    pub fn add_lobby(&mut self, mut lobby: Lobby) -> LobbyId {
         let id = Uuid::new_v4();
 
-        self.lobbies.insert(
-            id,
-            Lobby {
-                settings: Settings {
-                    number_of_detectives,
-                },
-                players: vec![],
-            },
-        );
+        lobby.code = self.unused_code();
+        self.codes.insert(lobby.code.clone(), id);
+        self.persistence.save_lobby(&id, &lobby);
+        self.lobbies.insert(id, lobby);
 
         id
     }
 
+    fn unused_code(&self) -> String {
+        let mut rng = rand::rng();
+
+        loop {
+            let code: String = (0..CODE_LENGTH)
+                .map(|_| CODE_CHARACTERS[rng.random_range(0..CODE_CHARACTERS.len())] as char)
+                .collect();
+
+            if !self.codes.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+
+    pub fn resolve(&self, reference: &LobbyReference) -> Result<LobbyId, LobbyServiceError> {
+        match reference {
+            LobbyReference::Id(id) => self.lobbies.contains_key(id).then_some(*id),
+            LobbyReference::Code(code) => self.codes.get(&code.to_uppercase()).copied(),
+        }
+        .ok_or(LobbyServiceError::UnknownLobby)
+    }
+
+    /// public lobbies which can still be joined
+    pub fn public_lobbies(&self) -> Vec<LobbyInfo> {
+        self.lobbies
+            .iter()
+            .filter(|(_, lobby)| {
+                lobby.settings.public && lobby.rematch.is_none() && !lobby.is_full()
+            })
+            .map(|(id, lobby)| LobbyInfo {
+                id: *id,
+                code: lobby.code.clone(),
+                players: lobby.players.len(),
+                settings: lobby.settings.clone(),
+            })
+            .collect()
+    }
+
+    pub fn lobby_ids(&self) -> Vec<LobbyId> {
+        self.lobbies.keys().copied().collect()
+    }
+
     pub fn get_lobby(&self, lobby_id: &LobbyId) -> Result<&Lobby, LobbyServiceError> {
         self.lobbies
             .get(lobby_id)
@@ -65,19 +331,116 @@ impl LobbyService {
         connection_id: Uuid,
         lobby_id: &LobbyId,
         sender: Sender<ServerPacket>,
+        options: PlayerOptions,
+        bot: Option<String>,
     ) -> Result<(), LobbyServiceError> {
         let lobby = self
             .lobbies
             .get_mut(lobby_id)
             .ok_or(LobbyServiceError::UnknownLobby)?;
+
+        if lobby.rematch.is_some() {
+            return Err(LobbyServiceError::ReservedForRematch);
+        }
+
+        if lobby.is_full() {
+            return Err(LobbyServiceError::LobbyFull);
+        }
+
         lobby.players.push(Player {
             uuid: connection_id,
+            session: Uuid::new_v4(),
             ws_sender: sender,
+            options,
+            bot,
         });
+        self.persistence.save_lobby(lobby_id, lobby);
+
         Ok(())
     }
 
-    pub fn close_lobby(&mut self, lobby_id: &LobbyId) {
-        self.lobbies.remove(lobby_id);
+    pub fn bot_seats(&self) -> Vec<BotSeat> {
+        self.lobbies
+            .iter()
+            .flat_map(|(id, lobby)| lobby.bot_seats(id))
+            .collect()
+    }
+
+    /// finds the lobby and player a session belongs to
+    pub fn find_session(&self, session: &Uuid) -> Option<(LobbyId, PlayerId)> {
+        self.lobbies.iter().find_map(|(id, lobby)| {
+            lobby
+                .players
+                .iter()
+                .find(|player| player.session == *session)
+                .map(|player| (*id, player.uuid))
+        })
+    }
+
+    /// replaces the connection of a player which resumed its session
+    pub fn reconnect(
+        &mut self,
+        lobby_id: &LobbyId,
+        player_id: PlayerId,
+        sender: Sender<ServerPacket>,
+    ) -> Result<&Lobby, LobbyServiceError> {
+        let lobby = self
+            .lobbies
+            .get_mut(lobby_id)
+            .ok_or(LobbyServiceError::UnknownLobby)?;
+
+        if let Some(player) = lobby
+            .players
+            .iter_mut()
+            .find(|player| player.uuid == player_id)
+        {
+            player.ws_sender = sender;
+        }
+
+        Ok(lobby)
+    }
+
+    /// returns true once every player of the lobby accepted the rematch
+    pub fn accept_rematch(
+        &mut self,
+        lobby_id: &LobbyId,
+        player_id: PlayerId,
+    ) -> Result<bool, LobbyServiceError> {
+        let lobby = self
+            .lobbies
+            .get_mut(lobby_id)
+            .ok_or(LobbyServiceError::UnknownLobby)?;
+
+        let accepted = lobby.rematch.as_mut().ok_or(LobbyServiceError::NoRematch)?;
+        accepted.insert(player_id);
+
+        let all_accepted = lobby
+            .players
+            .iter()
+            .all(|player| accepted.contains(&player.uuid));
+        self.persistence.save_lobby(lobby_id, lobby);
+
+        Ok(all_accepted)
+    }
+
+    /// removes the lobby if it has enough players and its map is still available
+    pub fn take_lobby(&mut self, lobby_id: &LobbyId) -> Result<Lobby, LobbyServiceError> {
+        let lobby = self.get_lobby(lobby_id)?;
+        if lobby.players.len() < 2 {
+            return Err(LobbyServiceError::NotEnoughPlayers);
+        }
+        if self.maps.get(&lobby.settings.map).is_none() {
+            return Err(LobbyServiceError::UnknownMap);
+        }
+
+        Ok(self.remove_lobby(lobby_id).unwrap())
+    }
+
+    pub fn remove_lobby(&mut self, lobby_id: &LobbyId) -> Option<Lobby> {
+        let lobby = self.lobbies.remove(lobby_id)?;
+        self.codes.remove(&lobby.code);
+        self.persistence.remove_lobby(lobby_id);
+
+        Some(lobby)
     }
 }
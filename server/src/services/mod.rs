@@ -1,4 +1,6 @@
 pub mod data;
 pub mod game;
 pub mod lobby;
+pub mod persistence;
+pub mod replay;
 pub mod ws_connection;
@@ -0,0 +1,215 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use tokio::{sync::watch, task};
+use uuid::Uuid;
+
+const LOBBIES_DIR: &str = "lobbies";
+const GAMES_DIR: &str = "games";
+
+pub type PersistenceServiceHandle = Arc<PersistenceService>;
+
+#[derive(Error, Debug)]
+pub enum PersistenceError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// writes of a single file, the lock keeps them from overlapping
+#[derive(Default)]
+struct FileWrites {
+    lock: Arc<tokio::sync::Mutex<()>>,
+    latest: u64,
+    pending: usize,
+}
+
+/// numbers every write in the order it was requested, so a flush knows which ones it waits for
+#[derive(Default)]
+struct Writes {
+    started: u64,
+    pending: BTreeSet<u64>,
+}
+
+/// stores lobbies and running games as json files below the data directory
+///
+/// writes run in the background so syncing them never blocks the services,
+/// `flush` waits for them
+pub struct PersistenceService {
+    data_dir: PathBuf,
+    files: Arc<std::sync::Mutex<HashMap<PathBuf, FileWrites>>>,
+    writes: Arc<watch::Sender<Writes>>,
+}
+
+impl PersistenceService {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            files: Arc::default(),
+            writes: Arc::new(watch::Sender::new(Writes::default())),
+        }
+    }
+
+    pub fn save_lobby(&self, id: &Uuid, lobby: &impl Serialize) {
+        self.save(LOBBIES_DIR, id, lobby);
+    }
+
+    pub fn remove_lobby(&self, id: &Uuid) {
+        self.write(self.path(LOBBIES_DIR, id), None);
+    }
+
+    pub fn load_lobbies<T: DeserializeOwned>(&self) -> Vec<(Uuid, T)> {
+        self.load(LOBBIES_DIR)
+    }
+
+    pub fn save_game(&self, id: &Uuid, game: &impl Serialize) {
+        self.save(GAMES_DIR, id, game);
+    }
+
+    pub fn remove_game(&self, id: &Uuid) {
+        self.write(self.path(GAMES_DIR, id), None);
+    }
+
+    pub fn load_games<T: DeserializeOwned>(&self) -> Vec<(Uuid, T)> {
+        self.load(GAMES_DIR)
+    }
+
+    /// waits until every write started so far is done, later writes are not waited for
+    pub async fn flush(&self) {
+        let started = self.writes.borrow().started;
+        let _ = self
+            .writes
+            .subscribe()
+            .wait_for(|writes| writes.pending.first().is_none_or(|write| *write > started))
+            .await;
+    }
+
+    fn path(&self, kind: &str, id: &Uuid) -> PathBuf {
+        self.data_dir.join(kind).join(format!("{id}.json"))
+    }
+
+    fn save(&self, kind: &str, id: &Uuid, value: &impl Serialize) {
+        match serde_json::to_vec(value) {
+            Ok(data) => self.write(self.path(kind, id), Some(data)),
+            Err(err) => tracing::warn!("failed to save {kind} {id}: {err}"),
+        }
+    }
+
+    /// writes the data or removes the file if there is none, a write is skipped
+    /// if a newer one of the same file was requested in the meantime
+    fn write(&self, path: PathBuf, data: Option<Vec<u8>>) {
+        let (lock, version) = {
+            let mut files = self.files.lock().unwrap();
+            let file = files.entry(path.clone()).or_default();
+            file.latest += 1;
+            file.pending += 1;
+            (file.lock.clone(), file.latest)
+        };
+        let mut number = 0;
+        self.writes.send_modify(|writes| {
+            writes.started += 1;
+            number = writes.started;
+            writes.pending.insert(number);
+        });
+
+        let files = self.files.clone();
+        let writes = self.writes.clone();
+        tokio::spawn(async move {
+            let guard = lock.lock().await;
+            let latest = files.lock().unwrap()[&path].latest;
+            if latest == version {
+                let file = path.clone();
+                let result = task::spawn_blocking(move || match data {
+                    Some(data) => Self::try_save(&file, &data),
+                    None => Self::try_remove(&file),
+                })
+                .await;
+                if let Ok(Err(err)) = result {
+                    tracing::warn!("failed to write {}: {err}", path.display());
+                }
+            }
+            drop(guard);
+
+            let mut files = files.lock().unwrap();
+            let file = files.get_mut(&path).unwrap();
+            file.pending -= 1;
+            if file.pending == 0 {
+                files.remove(&path);
+            }
+            drop(files);
+
+            writes.send_modify(|writes| {
+                writes.pending.remove(&number);
+            });
+        });
+    }
+
+    /// writes and syncs a temporary file first and syncs the directory after renaming it,
+    /// so neither a crash nor a power loss leaves a partially written file behind
+    fn try_save(path: &Path, data: &[u8]) -> Result<(), PersistenceError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, path)?;
+
+        // the rename is only durable once the directory entry is synced
+        #[cfg(unix)]
+        if let Some(dir) = path.parent() {
+            fs::File::open(dir)?.sync_all()?;
+        }
+
+        Ok(())
+    }
+
+    fn try_remove(path: &Path) -> Result<(), PersistenceError> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// reads every stored entry, entries which can't be read are skipped
+    fn load<T: DeserializeOwned>(&self, kind: &str) -> Vec<(Uuid, T)> {
+        let Ok(entries) = fs::read_dir(self.data_dir.join(kind)) else {
+            return vec![];
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_str()?.parse().ok()?;
+
+                match Self::read(&path) {
+                    Ok(value) => Some((id, value)),
+                    Err(err) => {
+                        tracing::warn!("failed to load {}: {err}", path.display());
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn read<T: DeserializeOwned>(path: &Path) -> Result<T, PersistenceError> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use game::replay::Replay;
+use uuid::Uuid;
+
+use crate::services::replay::{
+    ReplayFilter, ReplayInfo, ReplayPage, ReplayStore, ReplayStoreError, unix_seconds,
+};
+
+/// lists all stored replays, so listing them doesn't need to read every replay
+const INDEX_FILE: &str = "index.json";
+
+/// stores every replay as `{id}.json` in a directory
+pub struct FilesystemReplayStore {
+    replay_dir: PathBuf,
+    /// sorted by `finished_at`, oldest first
+    index: Mutex<Vec<ReplayInfo>>,
+}
+
+impl FilesystemReplayStore {
+    pub fn new(replay_dir: impl Into<PathBuf>) -> Self {
+        let replay_dir = replay_dir.into();
+
+        let mut index: Vec<ReplayInfo> = fs::read(replay_dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_else(|| Self::rebuild_index(&replay_dir));
+        index.sort_by_key(|info| info.finished_at);
+
+        Self {
+            replay_dir,
+            index: Mutex::new(index),
+        }
+    }
+
+    /// reads the replays written before the index existed
+    fn rebuild_index(replay_dir: &Path) -> Vec<ReplayInfo> {
+        let Ok(entries) = fs::read_dir(replay_dir) else {
+            return vec![];
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path.file_stem()?.to_str()?.parse().ok()?;
+                let replay: Replay = serde_json::from_slice(&fs::read(&path).ok()?).ok()?;
+                let modified = entry.metadata().ok()?.modified().ok()?;
+
+                Some(ReplayInfo {
+                    finished_at: unix_seconds(modified),
+                    ..ReplayInfo::new(id, &replay)
+                })
+            })
+            .collect()
+    }
+
+    fn replay_path(&self, id: &Uuid) -> PathBuf {
+        self.replay_dir.join(format!("{id}.json"))
+    }
+}
+
+impl ReplayStore for FilesystemReplayStore {
+    fn save(&self, id: Uuid, replay: &Replay) -> Result<(), ReplayStoreError> {
+        fs::create_dir_all(&self.replay_dir)?;
+        fs::write(self.replay_path(&id), serde_json::to_string_pretty(replay)?)?;
+
+        let mut index = self.index.lock().unwrap();
+        index.retain(|info| info.id != id);
+        index.push(ReplayInfo::new(id, replay));
+
+        let tmp_path = self.replay_dir.join(format!("{INDEX_FILE}.tmp"));
+        fs::write(&tmp_path, serde_json::to_vec(&*index)?)?;
+        fs::rename(&tmp_path, self.replay_dir.join(INDEX_FILE))?;
+
+        Ok(())
+    }
+
+    fn list(
+        &self,
+        filter: &ReplayFilter,
+        page: usize,
+        per_page: usize,
+    ) -> Result<ReplayPage, ReplayStoreError> {
+        let index = self.index.lock().unwrap();
+
+        Ok(ReplayPage::from_sorted(
+            index.iter().rev(),
+            filter,
+            page,
+            per_page,
+        ))
+    }
+
+    fn get(&self, id: &Uuid) -> Result<Replay, ReplayStoreError> {
+        if !self.index.lock().unwrap().iter().any(|info| info.id == *id) {
+            return Err(ReplayStoreError::UnknownReplay);
+        }
+
+        Ok(serde_json::from_slice(&fs::read(self.replay_path(id))?)?)
+    }
+}
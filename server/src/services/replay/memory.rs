@@ -0,0 +1,50 @@
+use std::sync::Mutex;
+
+use game::replay::Replay;
+use uuid::Uuid;
+
+use crate::services::replay::{
+    ReplayFilter, ReplayInfo, ReplayPage, ReplayStore, ReplayStoreError,
+};
+
+/// keeps replays in memory only, they are lost on restart
+#[derive(Default)]
+pub struct MemoryReplayStore {
+    replays: Mutex<Vec<(ReplayInfo, Replay)>>,
+}
+
+impl ReplayStore for MemoryReplayStore {
+    fn save(&self, id: Uuid, replay: &Replay) -> Result<(), ReplayStoreError> {
+        let mut replays = self.replays.lock().unwrap();
+        replays.retain(|(info, _)| info.id != id);
+        replays.push((ReplayInfo::new(id, replay), replay.clone()));
+
+        Ok(())
+    }
+
+    fn list(
+        &self,
+        filter: &ReplayFilter,
+        page: usize,
+        per_page: usize,
+    ) -> Result<ReplayPage, ReplayStoreError> {
+        let replays = self.replays.lock().unwrap();
+
+        Ok(ReplayPage::from_sorted(
+            replays.iter().rev().map(|(info, _)| info),
+            filter,
+            page,
+            per_page,
+        ))
+    }
+
+    fn get(&self, id: &Uuid) -> Result<Replay, ReplayStoreError> {
+        self.replays
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(info, _)| info.id == *id)
+            .map(|(_, replay)| replay.clone())
+            .ok_or(ReplayStoreError::UnknownReplay)
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use game::{event::Role, replay::Replay};
+use packets::{ErrorCode, ToErrorCode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub mod filesystem;
+pub mod memory;
+pub mod sqlite;
+
+pub type ReplayStoreHandle = Arc<dyn ReplayStore>;
+
+/// storage for replays of finished games, calls may block
+pub trait ReplayStore: Send + Sync {
+    fn save(&self, id: Uuid, replay: &Replay) -> Result<(), ReplayStoreError>;
+    /// newest replays first
+    fn list(
+        &self,
+        filter: &ReplayFilter,
+        page: usize,
+        per_page: usize,
+    ) -> Result<ReplayPage, ReplayStoreError>;
+    fn get(&self, id: &Uuid) -> Result<Replay, ReplayStoreError>;
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayStoreError {
+    #[error("unknown replay")]
+    UnknownReplay,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+}
+
+impl ToErrorCode for ReplayStoreError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::Internal
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayInfo {
+    pub id: Uuid,
+    pub winner: Role,
+    /// unix timestamp in seconds
+    pub finished_at: u64,
+    pub number_of_detectives: usize,
+}
+
+impl ReplayInfo {
+    /// info for a replay of a game which just finished
+    pub fn new(id: Uuid, replay: &Replay) -> Self {
+        Self {
+            id,
+            winner: replay.winner.clone(),
+            finished_at: unix_seconds(SystemTime::now()),
+            number_of_detectives: replay.detective_starting_stations.len(),
+        }
+    }
+
+    fn matches(&self, filter: &ReplayFilter) -> bool {
+        filter
+            .winner
+            .as_ref()
+            .is_none_or(|winner| self.winner == *winner)
+            && filter.from.is_none_or(|from| self.finished_at >= from)
+            && filter.to.is_none_or(|to| self.finished_at <= to)
+    }
+}
+
+#[derive(Default)]
+pub struct ReplayFilter {
+    pub winner: Option<Role>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ReplayPage {
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+    pub replays: Vec<ReplayInfo>,
+}
+
+impl ReplayPage {
+    /// filters and paginates replays which are already sorted newest first
+    fn from_sorted<'a>(
+        infos: impl Iterator<Item = &'a ReplayInfo>,
+        filter: &ReplayFilter,
+        page: usize,
+        per_page: usize,
+    ) -> Self {
+        let matching: Vec<_> = infos.filter(|info| info.matches(filter)).collect();
+
+        Self {
+            total: matching.len(),
+            page,
+            per_page,
+            replays: matching
+                .into_iter()
+                .skip(page.saturating_mul(per_page))
+                .take(per_page)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,161 @@
+use std::{path::Path, sync::Mutex};
+
+use game::{event::Role, replay::Replay};
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use crate::services::replay::{
+    ReplayFilter, ReplayInfo, ReplayPage, ReplayStore, ReplayStoreError,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS replays (
+        id BLOB PRIMARY KEY,
+        winner TEXT NOT NULL,
+        finished_at INTEGER NOT NULL,
+        number_of_detectives INTEGER NOT NULL,
+        replay TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS replays_finished_at ON replays (finished_at);
+";
+
+/// unset filters match every replay
+const FILTER: &str = "
+    (?1 IS NULL OR winner = ?1)
+    AND (?2 IS NULL OR finished_at >= ?2)
+    AND (?3 IS NULL OR finished_at <= ?3)
+";
+
+/// stores replays in a single SQLite database
+pub struct SqliteReplayStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteReplayStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayStoreError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self, ReplayStoreError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, ReplayStoreError> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+fn role_to_sql(role: &Role) -> Result<String, ReplayStoreError> {
+    Ok(serde_json::to_value(role)?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+fn role_from_sql(role: String) -> Result<Role, ReplayStoreError> {
+    Ok(serde_json::from_value(serde_json::Value::String(role))?)
+}
+
+impl ReplayStore for SqliteReplayStore {
+    fn save(&self, id: Uuid, replay: &Replay) -> Result<(), ReplayStoreError> {
+        let info = ReplayInfo::new(id, replay);
+
+        self.connection.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO replays (id, winner, finished_at, number_of_detectives, replay)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                role_to_sql(&info.winner)?,
+                info.finished_at as i64,
+                info.number_of_detectives as i64,
+                serde_json::to_string(replay)?,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn list(
+        &self,
+        filter: &ReplayFilter,
+        page: usize,
+        per_page: usize,
+    ) -> Result<ReplayPage, ReplayStoreError> {
+        let connection = self.connection.lock().unwrap();
+
+        let winner = filter.winner.as_ref().map(role_to_sql).transpose()?;
+        let from = filter.from.map(|from| from as i64);
+        let to = filter.to.map(|to| to as i64);
+
+        let total: i64 = connection.query_row(
+            &format!("SELECT COUNT(*) FROM replays WHERE {FILTER}"),
+            params![winner, from, to],
+            |row| row.get(0),
+        )?;
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT id, winner, finished_at, number_of_detectives FROM replays
+            WHERE {FILTER}
+            ORDER BY finished_at DESC, rowid DESC
+            LIMIT ?4 OFFSET ?5"
+        ))?;
+
+        let rows = statement.query_map(
+            params![
+                winner,
+                from,
+                to,
+                per_page as i64,
+                i64::try_from(page.saturating_mul(per_page)).unwrap_or(i64::MAX)
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, Uuid>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+
+        let mut replays = vec![];
+        for row in rows {
+            let (id, winner, finished_at, number_of_detectives) = row?;
+
+            replays.push(ReplayInfo {
+                id,
+                winner: role_from_sql(winner)?,
+                finished_at: finished_at as u64,
+                number_of_detectives: number_of_detectives as usize,
+            });
+        }
+
+        Ok(ReplayPage {
+            total: total as usize,
+            page,
+            per_page,
+            replays,
+        })
+    }
+
+    fn get(&self, id: &Uuid) -> Result<Replay, ReplayStoreError> {
+        let replay: Option<String> = self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT replay FROM replays WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let replay = replay.ok_or(ReplayStoreError::UnknownReplay)?;
+
+        Ok(serde_json::from_str(&replay)?)
+    }
+}
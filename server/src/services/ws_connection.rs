@@ -1,7 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use thiserror::Error;
-use tokio::sync::{Mutex, mpsc::Sender};
+use tokio::sync::{Mutex, mpsc::Sender, oneshot};
 use uuid::Uuid;
 
 use packets::ServerPacket;
@@ -11,7 +11,12 @@ pub type WsConnectionServiceHandle = Arc<Mutex<WsConnectionService>>;
 pub struct ConnectionData {
     lobby_id: Option<Uuid>,
     game_id: Option<Uuid>,
+    rematch_id: Option<Uuid>,
+    /// lobbies created on this connection, they are removed with it if nobody joined them
+    created_lobbies: Vec<Uuid>,
     ws_sender: Sender<ServerPacket>,
+    /// notifies the socket that its player was taken over by another connection
+    replaced: oneshot::Sender<()>,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -26,16 +31,88 @@ pub struct WsConnectionService {
 }
 
 impl WsConnectionService {
-    pub fn add_connection(&mut self, connection_id: Uuid, ws_sender: Sender<ServerPacket>) {
+    pub fn add_connection(
+        &mut self,
+        connection_id: Uuid,
+        ws_sender: Sender<ServerPacket>,
+        replaced: oneshot::Sender<()>,
+    ) {
         self.connections.insert(
             connection_id,
             ConnectionData {
                 lobby_id: None,
                 game_id: None,
+                rematch_id: None,
+                created_lobbies: vec![],
                 ws_sender,
+                replaced,
             },
         );
     }
+
+    /// moves the connection to the id of a player, a previous connection of the player
+    /// is told that it was replaced so it can be closed
+    pub fn take_over(
+        &mut self,
+        connection_id: Uuid,
+        player_id: Uuid,
+    ) -> Result<(), WsConnectionServiceError> {
+        let data = self
+            .connections
+            .remove(&connection_id)
+            .ok_or(WsConnectionServiceError::UnknownConnection)?;
+
+        if let Some(previous) = self.connections.remove(&player_id) {
+            let _ = previous.replaced.send(());
+        }
+
+        self.add_connection(player_id, data.ws_sender, data.replaced);
+        self.connections
+            .get_mut(&player_id)
+            .unwrap()
+            .created_lobbies = data.created_lobbies;
+        Ok(())
+    }
+
+    pub fn remove_connection(&mut self, connection_id: Uuid) {
+        self.connections.remove(&connection_id);
+    }
+
+    /// removes the connection of a closed socket, unless another connection took over its
+    /// player in the meantime, and returns the lobbies it was in or created
+    pub fn close(&mut self, ws_sender: &Sender<ServerPacket>) -> Vec<Uuid> {
+        let Some(connection_id) = self
+            .connections
+            .iter()
+            .find(|(_, data)| data.ws_sender.same_channel(ws_sender))
+            .map(|(connection_id, _)| *connection_id)
+        else {
+            return vec![];
+        };
+
+        let data = self.connections.remove(&connection_id).unwrap();
+        let mut lobbies = data.created_lobbies;
+        lobbies.extend(data.lobby_id);
+        lobbies.extend(data.rematch_id);
+        lobbies
+    }
+
+    pub fn add_created_lobby(
+        &mut self,
+        connection_id: Uuid,
+        lobby_id: Uuid,
+    ) -> Result<(), WsConnectionServiceError> {
+        self.connections
+            .get_mut(&connection_id)
+            .ok_or(WsConnectionServiceError::UnknownConnection)
+            .map(|data| data.created_lobbies.push(lobby_id))
+    }
+
+    /// whether a socket or bot currently plays as the player
+    pub fn is_connected(&self, connection_id: Uuid) -> bool {
+        self.connections.contains_key(&connection_id)
+    }
+
     pub fn ws_sender(
         &self,
         connection_id: Uuid,
@@ -81,4 +158,25 @@ impl WsConnectionService {
             .ok_or(WsConnectionServiceError::UnknownConnection)
             .map(|data| data.game_id = game_id)
     }
+
+    pub fn rematch_id(
+        &self,
+        connection_id: Uuid,
+    ) -> Result<Option<Uuid>, WsConnectionServiceError> {
+        self.connections
+            .get(&connection_id)
+            .ok_or(WsConnectionServiceError::UnknownConnection)
+            .map(|data| data.rematch_id)
+    }
+
+    pub fn set_rematch_id(
+        &mut self,
+        connection_id: Uuid,
+        rematch_id: Option<Uuid>,
+    ) -> Result<(), WsConnectionServiceError> {
+        self.connections
+            .get_mut(&connection_id)
+            .ok_or(WsConnectionServiceError::UnknownConnection)
+            .map(|data| data.rematch_id = rematch_id)
+    }
 }
@@ -0,0 +1,175 @@
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc};
+
+use game::map::MapError;
+pub use game::map::Rules;
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::Level;
+
+pub type SettingsHandle = Arc<Settings>;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SettingsError {
+    #[error("failed to read config file {0}: {1}")]
+    FailedToRead(PathBuf, String),
+    #[error("invalid config file: {0}")]
+    InvalidFile(String),
+    #[error("invalid value for {0}: {1}")]
+    InvalidEnv(&'static str, String),
+    #[error("invalid log level {0}")]
+    InvalidLogLevel(String),
+    #[error("invalid rules: {0}")]
+    InvalidRules(#[from] MapError),
+    #[error("limit {0} has to be at least 1")]
+    InvalidLimit(&'static str),
+}
+
+/// configuration of the server, read from a TOML file and overridden by environment variables
+///
+/// there are no timer defaults, as games have no turn timers yet
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub server: ServerSettings,
+    pub storage: StorageSettings,
+    /// rules of the built-in map, other maps bring their own
+    pub rules: Rules,
+    pub limits: Limits,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    pub address: SocketAddr,
+    pub log_level: String,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([0, 0, 0, 0], 8081)),
+            log_level: "info".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageSettings {
+    /// lobbies and running games are stored here to survive restarts
+    pub data_dir: PathBuf,
+    pub replay_dir: PathBuf,
+    /// stores replays in this SQLite database instead of `replay_dir`
+    pub replay_database: Option<PathBuf>,
+    /// every `.toml` file in it is loaded as an additional map named after the file
+    pub maps_dir: PathBuf,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            data_dir: "./state".into(),
+            replay_dir: "./replays".into(),
+            replay_database: None,
+            maps_dir: "./maps".into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub max_lobbies: usize,
+    pub max_spectators: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_lobbies: 1000,
+            max_spectators: 50,
+        }
+    }
+}
+
+impl Settings {
+    /// reads the config file if it exists, applies the environment and validates the result
+    pub fn load(
+        path: impl Into<PathBuf>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, SettingsError> {
+        let path = path.into();
+
+        let mut settings = match std::fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(err) => return Err(SettingsError::FailedToRead(path, err.to_string())),
+        };
+
+        settings.apply_env(env)?;
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, SettingsError> {
+        toml::from_str(content).map_err(|err| SettingsError::InvalidFile(err.message().to_string()))
+    }
+
+    pub fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), SettingsError> {
+        if let Some(address) = env("ADDRESS") {
+            self.server.address = parse_env("ADDRESS", &address)?;
+        }
+        if let Some(port) = env("PORT") {
+            self.server.address.set_port(parse_env("PORT", &port)?);
+        }
+        if let Some(log_level) = env("LOG_LEVEL") {
+            self.server.log_level = log_level;
+        }
+        if let Some(data_dir) = env("DATA_DIR") {
+            self.storage.data_dir = data_dir.into();
+        }
+        if let Some(replay_dir) = env("REPLAY_DIR") {
+            self.storage.replay_dir = replay_dir.into();
+        }
+        if let Some(replay_database) = env("REPLAY_DATABASE") {
+            self.storage.replay_database = Some(replay_database.into());
+        }
+        if let Some(maps_dir) = env("MAPS_DIR") {
+            self.storage.maps_dir = maps_dir.into();
+        }
+        if let Some(max_lobbies) = env("MAX_LOBBIES") {
+            self.limits.max_lobbies = parse_env("MAX_LOBBIES", &max_lobbies)?;
+        }
+        if let Some(max_spectators) = env("MAX_SPECTATORS") {
+            self.limits.max_spectators = parse_env("MAX_SPECTATORS", &max_spectators)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        self.log_level()?;
+        self.rules.validate()?;
+
+        if self.limits.max_lobbies == 0 {
+            return Err(SettingsError::InvalidLimit("max_lobbies"));
+        }
+        if self.limits.max_spectators == 0 {
+            return Err(SettingsError::InvalidLimit("max_spectators"));
+        }
+
+        Ok(())
+    }
+
+    pub fn log_level(&self) -> Result<Level, SettingsError> {
+        Level::from_str(&self.server.log_level)
+            .map_err(|_| SettingsError::InvalidLogLevel(self.server.log_level.clone()))
+    }
+}
+
+fn parse_env<T: FromStr>(name: &'static str, value: &str) -> Result<T, SettingsError> {
+    value
+        .parse()
+        .map_err(|_| SettingsError::InvalidEnv(name, value.to_string()))
+}
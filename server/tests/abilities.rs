@@ -12,8 +12,8 @@ mod common;
 
 #[tokio::test]
 async fn mister_x_hidden() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     for _ in 0..2 {
         game.hidden_move_mister_x(110).await;
@@ -80,8 +80,8 @@ impl GameConnection {
 
 #[tokio::test]
 async fn mister_x_double() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     game.double_move_mister_x().await;
     game.full_move_detectives(
@@ -136,8 +136,8 @@ impl GameConnection {
 
 #[tokio::test]
 async fn detective_undeground() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     for _ in 0..2 {
         game.full_move_mister_x(110).await;
@@ -0,0 +1,167 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{
+    connection::{create_game, create_game_with_settings},
+    data::Game,
+    test_server,
+    ws::{
+        assert_receive_error, assert_receive_message, get_ws_connection, receive_any_message,
+        send_message,
+    },
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartMove {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+#[tokio::test]
+async fn lists_bots() {
+    let (server, _dir) = test_server();
+
+    let response = server.get("/game/bots").await;
+
+    response.assert_status_ok();
+    assert_eq!(response.json::<Vec<String>>(), vec!["random"]);
+}
+
+#[tokio::test]
+async fn can_play_against_bot() {
+    let (server, _dir) = test_server();
+
+    let mut player = get_ws_connection(&server).await;
+    let game_id = create_game(&mut player).await;
+
+    send_message(&mut player, "joinGame", Some(json!({ "id": game_id }))).await;
+    send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+    send_message(&mut player, "startGame", None).await;
+
+    let role = assert_receive_message::<GameStarted>(&mut player, "gameStarted")
+        .await
+        .unwrap()
+        .role;
+
+    let message = assert_receive_message::<StartMove>(&mut player, "startMove").await;
+    assert_eq!(message.unwrap().role, "mister_x");
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+
+    if role == "mister_x" {
+        send_message(
+            &mut player,
+            "moveMisterX",
+            Some(json!([{ "station_id": 120, "transport_type": "hidden" }])),
+        )
+        .await;
+        send_message(&mut player, "submitMove", None).await;
+
+        assert_receive_message::<EndMove>(&mut player, "endMove").await;
+        let message = assert_receive_message::<StartMove>(&mut player, "startMove").await;
+        assert_eq!(message.unwrap().role, "detective");
+        assert_receive_message::<Game>(&mut player, "gameState").await;
+
+        for _ in 0..4 {
+            assert_receive_message::<Game>(&mut player, "gameState").await;
+        }
+    }
+
+    assert_receive_message::<EndMove>(&mut player, "endMove").await;
+    let message = assert_receive_message::<StartMove>(&mut player, "startMove").await;
+    assert_eq!(message.unwrap().role, role);
+
+    let state = assert_receive_message::<Game>(&mut player, "gameState")
+        .await
+        .unwrap();
+    if role == "detective" {
+        assert_eq!(state.mister_x.moves.len(), 1);
+    } else {
+        assert_eq!(state.round, 1);
+    }
+}
+
+#[tokio::test]
+async fn bot_plays_full_game_as_only_detective() {
+    let (server, _dir) = test_server();
+
+    // Mister X is chosen randomly, so lobbies are created until the bot is the detective
+    let mut player = loop {
+        let mut player = get_ws_connection(&server).await;
+        let game_id =
+            create_game_with_settings(&mut player, json!({ "number_of_detectives": 1 })).await;
+
+        send_message(
+            &mut player,
+            "joinGame",
+            Some(json!({ "id": game_id, "legal_moves": true })),
+        )
+        .await;
+        send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+        send_message(&mut player, "startGame", None).await;
+
+        let role = assert_receive_message::<GameStarted>(&mut player, "gameStarted")
+            .await
+            .unwrap()
+            .role;
+        if role == "mister_x" {
+            break player;
+        }
+    };
+
+    let mut detective_moves = 0;
+    let winner = loop {
+        let (name, content) = receive_any_message(&mut player).await;
+        match name.as_str() {
+            "legalMoves" => {
+                let content = content.unwrap();
+                let moves = content["moves"].as_array().unwrap();
+                let next = moves
+                    .iter()
+                    .find(|next| next["transport_type"] != "hidden")
+                    .unwrap_or(&moves[0]);
+
+                send_message(&mut player, "moveMisterX", Some(json!([next]))).await;
+                send_message(&mut player, "submitMove", None).await;
+            }
+            "startMove" if content.as_ref().unwrap()["role"] == "detective" => detective_moves += 1,
+            "gameEnded" => break content.unwrap()["winner"].clone(),
+            _ => {}
+        }
+    };
+
+    assert!(detective_moves > 0);
+    assert!(winner == "detective" || winner == "mister_x");
+}
+
+#[tokio::test]
+async fn can_not_add_unknown_bot() {
+    let (server, _dir) = test_server();
+
+    let mut player = get_ws_connection(&server).await;
+    let game_id = create_game(&mut player).await;
+
+    send_message(&mut player, "joinGame", Some(json!({ "id": game_id }))).await;
+    send_message(&mut player, "addBot", Some(json!({ "name": "unknown" }))).await;
+
+    assert_receive_error(&mut player, "unknown bot").await;
+}
+
+#[tokio::test]
+async fn can_not_add_bot_outside_of_lobby() {
+    let (server, _dir) = test_server();
+
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+
+    assert_receive_error(&mut player, "not in lobby").await;
+}
@@ -15,8 +15,8 @@ struct EndMove;
 
 #[tokio::test]
 async fn can_move() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(
         &mut game.mister_x,
@@ -67,8 +67,8 @@ async fn can_move() {
 
 #[tokio::test]
 async fn non_active_can_not_send_or_submit_move() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(
         &mut game.detective,
@@ -77,7 +77,7 @@ async fn non_active_can_not_send_or_submit_move() {
     )
     .await;
 
-    assert_receive_error(&mut game.detective, "not your turn").await;
+    assert_receive_error(&mut game.detective, "not your role").await;
 
     send_message(
         &mut game.detective,
@@ -114,7 +114,7 @@ async fn non_active_can_not_send_or_submit_move() {
         Some(json!({ "color": colors[0], "station_id": 106, "transport_type": "taxi" })),
     )
     .await;
-    assert_receive_error(&mut game.mister_x, "not your turn").await;
+    assert_receive_error(&mut game.mister_x, "not your role").await;
 
     send_message(
         &mut game.mister_x,
@@ -143,8 +143,8 @@ async fn non_active_can_not_send_or_submit_move() {
 
 #[tokio::test]
 async fn can_only_submit_if_all_moved() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(&mut game.mister_x, "submitMove", None).await;
     assert_receive_error(&mut game.mister_x, "not all moved").await;
@@ -196,8 +196,8 @@ async fn can_only_submit_if_all_moved() {
 
 #[tokio::test]
 async fn can_change_move() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(
         &mut game.mister_x,
@@ -265,8 +265,8 @@ async fn can_change_move() {
 
 #[tokio::test]
 async fn can_double_move() {
-    let (mut server, _dir) = test_server();
-    let mut game = start_game(&mut server).await;
+    let (server, _dir) = test_server();
+    let mut game = start_game(&server).await;
 
     game.receive_start_move_message("mister_x").await;
 
@@ -305,8 +305,8 @@ async fn can_double_move() {
 
 #[tokio::test]
 async fn can_move_hidden() {
-    let (mut server, _dir) = test_server();
-    let mut game = start_game(&mut server).await;
+    let (server, _dir) = test_server();
+    let mut game = start_game(&server).await;
 
     game.receive_start_move_message("mister_x").await;
 
@@ -338,8 +338,8 @@ async fn can_move_hidden() {
 
 #[tokio::test]
 async fn can_only_do_valid_moves() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(
         &mut game.mister_x,
@@ -3,19 +3,25 @@ use serde::Deserialize;
 use serde_json::json;
 
 use crate::common::{
-    data::Game,
+    data::{Game, LobbyState},
     ws::{assert_receive_message, get_ws_connection, send_message},
 };
 
 pub async fn create_game(socket: &mut TestWebSocket) -> String {
-    send_message(
+    create_game_with_settings(
         socket,
-        "createGame",
-        Some(json!({
+        json!({
             "number_of_detectives": 4,
-        })),
+        }),
     )
-    .await;
+    .await
+}
+
+pub async fn create_game_with_settings(
+    socket: &mut TestWebSocket,
+    settings: serde_json::Value,
+) -> String {
+    send_message(socket, "createGame", Some(settings)).await;
 
     #[derive(Debug, Deserialize)]
     struct GameCreated {
@@ -28,15 +34,29 @@ pub async fn create_game(socket: &mut TestWebSocket) -> String {
 }
 
 pub struct GameConnection {
+    pub id: String,
     pub mister_x: TestWebSocket,
     pub detective: TestWebSocket,
 }
 
-pub async fn start_game(server: &mut TestServer) -> GameConnection {
+pub async fn start_game(server: &TestServer) -> GameConnection {
+    start_game_with_settings(
+        server,
+        json!({
+            "number_of_detectives": 4,
+        }),
+    )
+    .await
+}
+
+pub async fn start_game_with_settings(
+    server: &TestServer,
+    settings: serde_json::Value,
+) -> GameConnection {
     let mut player_1 = get_ws_connection(server).await;
     let mut player_2 = get_ws_connection(server).await;
 
-    let game_id = create_game(&mut player_1).await;
+    let game_id = create_game_with_settings(&mut player_1, settings).await;
 
     send_message(&mut player_1, "joinGame", Some(json!({ "id": game_id }))).await;
     send_message(&mut player_2, "joinGame", Some(json!({ "id": game_id }))).await;
@@ -55,19 +75,34 @@ pub async fn start_game(server: &mut TestServer) -> GameConnection {
 
     if role_1 == "detective" {
         GameConnection {
+            id: game_id,
             mister_x: player_2,
             detective: player_1,
         }
     } else {
         GameConnection {
+            id: game_id,
             mister_x: player_1,
             detective: player_2,
         }
     }
 }
 
-pub async fn start_game_with_colors(server: &mut TestServer) -> (GameConnection, Vec<String>) {
-    let mut game = start_game(server).await;
+pub async fn start_game_with_colors(server: &TestServer) -> (GameConnection, Vec<String>) {
+    start_game_with_colors_and_settings(
+        server,
+        json!({
+            "number_of_detectives": 4,
+        }),
+    )
+    .await
+}
+
+pub async fn start_game_with_colors_and_settings(
+    server: &TestServer,
+    settings: serde_json::Value,
+) -> (GameConnection, Vec<String>) {
+    let mut game = start_game_with_settings(server, settings).await;
 
     game.receive_start_move_message("mister_x").await;
 
@@ -102,7 +137,7 @@ impl GameConnection {
         assert_eq!(message.unwrap().role, expected_role);
     }
 
-    pub async fn receive_game_ended_message(&mut self, expected_winner: &str) {
+    pub async fn receive_game_ended_message(&mut self, expected_winner: &str) -> LobbyState {
         #[derive(Debug, Deserialize)]
         struct GameEnded {
             winner: String,
@@ -123,6 +158,11 @@ impl GameConnection {
             .unwrap();
 
         assert!(data.mister_x.station_id.is_some());
+
+        assert_receive_message::<LobbyState>(&mut self.detective, "lobbyState").await;
+        assert_receive_message::<LobbyState>(&mut self.mister_x, "lobbyState")
+            .await
+            .unwrap()
     }
 
     pub async fn send_detective_move(
@@ -146,6 +186,25 @@ impl GameConnection {
             .unwrap()
     }
 
+    /// moves a detective onto Mister X, ending the game in the first round
+    pub async fn catch_mister_x(&mut self, colors: &[String]) -> LobbyState {
+        #[derive(Debug, Deserialize)]
+        struct EndMove;
+
+        self.full_move_mister_x(110).await;
+
+        let _ = self.send_detective_move(&colors[0], 110, "taxi").await;
+        let _ = self.send_detective_move(&colors[1], 107, "bus").await;
+        let _ = self.send_detective_move(&colors[2], 108, "bus").await;
+        let _ = self.send_detective_move(&colors[3], 109, "taxi").await;
+        send_message(&mut self.detective, "submitMove", None).await;
+
+        assert_receive_message::<EndMove>(&mut self.mister_x, "endMove").await;
+        assert_receive_message::<EndMove>(&mut self.detective, "endMove").await;
+
+        self.receive_game_ended_message("detective").await
+    }
+
     pub async fn double_move(&mut self, colors: &[String]) {
         self.full_move_mister_x(110).await;
 
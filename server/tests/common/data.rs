@@ -41,4 +41,18 @@ pub struct Game {
     pub players: Vec<PlayerGame>,
     pub mister_x: MisterXGame,
     pub round: u8,
+    pub seq: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LobbyPlayer {
+    pub id: String,
+    pub score: u32,
+    pub accepted_rematch: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LobbyState {
+    pub id: String,
+    pub players: Vec<LobbyPlayer>,
 }
@@ -6,9 +6,12 @@ use axum_test::TestServer;
 use game::data::{Connection, Round, Station, StationType};
 use server::{
     Settings, app,
-    services::data::{
-        DataServiceHandle, DataServiceTrait,
-        service::{self},
+    services::{
+        data::{
+            DataServiceHandle, DataServiceTrait, MapRegistry,
+            service::{self},
+        },
+        replay::{ReplayStoreHandle, memory::MemoryReplayStore},
     },
 };
 use tempfile::TempDir;
@@ -17,27 +20,94 @@ pub mod connection;
 pub mod data;
 pub mod ws;
 
-fn get_test_server(data_service: DataServiceHandle) -> (TestServer, TempDir) {
+fn get_test_server(
+    data_service: DataServiceHandle,
+    replay_store: ReplayStoreHandle,
+) -> (TestServer, TempDir) {
     let path = TempDir::new().unwrap();
 
-    let app = app(
-        data_service,
-        Arc::new(Settings {
-            replay_dir: path.path().to_path_buf(),
-        }),
-    );
-    (
-        TestServer::builder().http_transport().build(app).unwrap(),
+    (server_in_dir(data_service, replay_store, &path), path)
+}
+
+fn server_in_dir(
+    data_service: DataServiceHandle,
+    replay_store: ReplayStoreHandle,
+    path: &TempDir,
+) -> TestServer {
+    server_with_settings(
+        MapRegistry::new(data_service),
+        replay_store,
+        path,
+        Settings::default(),
+    )
+}
+
+fn server_with_settings(
+    maps: MapRegistry,
+    replay_store: ReplayStoreHandle,
+    path: &TempDir,
+    mut settings: Settings,
+) -> TestServer {
+    settings.storage.data_dir = path.path().join("data");
+    let app = app(Arc::new(maps), replay_store, Arc::new(settings));
+
+    TestServer::builder().http_transport().build(app).unwrap()
+}
+
+/// starts a new server on the data of a previous one, like after a restart
+pub fn restart_test_server(path: &TempDir) -> TestServer {
+    server_in_dir(
+        Arc::new(DataService),
+        Arc::new(MemoryReplayStore::default()),
         path,
     )
 }
 
 pub fn test_server() -> (TestServer, TempDir) {
-    get_test_server(Arc::new(DataService))
+    test_server_with_replay_store(Arc::new(MemoryReplayStore::default()))
+}
+
+pub fn test_server_with_replay_store(replay_store: ReplayStoreHandle) -> (TestServer, TempDir) {
+    get_test_server(Arc::new(DataService), replay_store)
 }
 
 pub fn test_prod_server() -> (TestServer, TempDir) {
-    get_test_server(Arc::new(service::DataService))
+    get_test_server(
+        Arc::new(service::DataService::default()),
+        Arc::new(MemoryReplayStore::default()),
+    )
+}
+
+pub fn test_server_with_settings(settings: Settings) -> (TestServer, TempDir) {
+    let path = TempDir::new().unwrap();
+    let server = server_with_settings(
+        test_maps(),
+        Arc::new(MemoryReplayStore::default()),
+        &path,
+        settings,
+    );
+
+    (server, path)
+}
+
+/// the test map is the default, the production map is available as `london-full`
+pub fn test_server_with_maps() -> (TestServer, TempDir) {
+    let mut maps = test_maps();
+    maps.insert("london-full", Arc::new(service::DataService::default()));
+
+    let path = TempDir::new().unwrap();
+    let server = server_with_settings(
+        maps,
+        Arc::new(MemoryReplayStore::default()),
+        &path,
+        Settings::default(),
+    );
+
+    (server, path)
+}
+
+fn test_maps() -> MapRegistry {
+    MapRegistry::new(Arc::new(DataService))
 }
 
 struct DataService;
@@ -51,6 +121,7 @@ impl DataServiceTrait for DataService {
                 id: 100 + i,
                 pos_x: 0,
                 pos_y: 0,
+                name: None,
                 types: vec![
                     StationType::Taxi,
                     StationType::Bus,
@@ -159,8 +230,14 @@ impl DataServiceTrait for DataService {
             .collect()
     }
 
-    fn get_colors(&self) -> [&str; 5] {
+    fn get_colors(&self) -> Vec<String> {
         ["red", "blue", "green", "yellow", "purple"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn get_detective_stations(&self) -> Vec<u8> {
+        vec![100, 101, 102, 103]
     }
 
     fn get_random_detective_stations(&self, count: usize) -> Vec<u8> {
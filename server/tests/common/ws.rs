@@ -89,3 +89,10 @@ pub async fn assert_receive_error(connection: &mut TestWebSocket, message: &str)
     assert_eq!(received_name, "error");
     assert_eq!(response.unwrap().message, message);
 }
+
+/// receives the next packet whatever its name, for tests reacting to packets in any order
+pub async fn receive_any_message(
+    connection: &mut TestWebSocket,
+) -> (String, Option<serde_json::Value>) {
+    receive_message(connection, "any").await
+}
@@ -13,8 +13,8 @@ struct EndMove;
 
 #[tokio::test]
 async fn can_lose() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     for _ in 0..3 {
         game.double_move(&colors).await;
@@ -0,0 +1,121 @@
+use axum_test::{TestWebSocket, WsMessage};
+use packets::{
+    ClientPacket, CreateGamePacket, HelloPacket, ServerPacket, SpectatorSettings,
+    encoding::{Encoding, encode},
+    version::PROTOCOL_VERSION,
+};
+use serde_json::json;
+
+use crate::common::{
+    test_server,
+    ws::{get_ws_connection, send_message},
+};
+
+mod common;
+
+async fn hello(player: &mut TestWebSocket, encoding: Encoding) {
+    send_message(
+        player,
+        "hello",
+        Some(json!({ "protocol_version": PROTOCOL_VERSION, "encoding": encoding })),
+    )
+    .await;
+
+    let ServerPacket::Welcome(welcome) = receive_binary(player, encoding).await else {
+        panic!("expected welcome");
+    };
+    assert_eq!(welcome.encoding, encoding);
+}
+
+async fn send_binary(player: &mut TestWebSocket, packet: ClientPacket, encoding: Encoding) {
+    player
+        .send_message(WsMessage::Binary(
+            packet.to_binary(encoding).unwrap().into(),
+        ))
+        .await;
+}
+
+async fn receive_binary(player: &mut TestWebSocket, encoding: Encoding) -> ServerPacket {
+    ServerPacket::from_binary(&player.receive_bytes().await, encoding).unwrap()
+}
+
+fn create_game() -> ClientPacket {
+    ClientPacket::CreateGame(CreateGamePacket {
+        number_of_detectives: 4,
+        spectators: SpectatorSettings::default(),
+        public: false,
+        map: None,
+    })
+}
+
+#[tokio::test]
+async fn negotiated_encodings_are_used_in_both_directions() {
+    let (server, _dir) = test_server();
+
+    for encoding in [Encoding::MessagePack, Encoding::Cbor] {
+        let mut player = get_ws_connection(&server).await;
+        hello(&mut player, encoding).await;
+
+        send_binary(&mut player, create_game(), encoding).await;
+        let ServerPacket::Game(game) = receive_binary(&mut player, encoding).await else {
+            panic!("expected game");
+        };
+        assert_eq!(game.code.len(), 6);
+
+        send_message(&mut player, "startGame", None).await;
+        let ServerPacket::Error(error) = receive_binary(&mut player, encoding).await else {
+            panic!("expected error");
+        };
+        assert_eq!(error.packet.as_deref(), Some("startGame"));
+    }
+}
+
+#[tokio::test]
+async fn binary_frames_carry_request_ids() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+    hello(&mut player, Encoding::MessagePack).await;
+
+    player
+        .send_message(WsMessage::Binary(
+            encode::<()>(Encoding::MessagePack, "startGame#3", None)
+                .unwrap()
+                .into(),
+        ))
+        .await;
+    let ServerPacket::Error(error) = receive_binary(&mut player, Encoding::MessagePack).await
+    else {
+        panic!("expected error");
+    };
+    assert_eq!(error.id.as_deref(), Some("3"));
+    assert_eq!(error.packet.as_deref(), Some("startGame"));
+
+    player
+        .send_message(WsMessage::Binary(vec![0xc1].into()))
+        .await;
+    let ServerPacket::Error(error) = receive_binary(&mut player, Encoding::MessagePack).await
+    else {
+        panic!("expected error");
+    };
+    assert_eq!(error.message, "invalid packet");
+    assert_eq!(error.packet, None);
+}
+
+#[tokio::test]
+async fn text_is_the_default() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_binary(
+        &mut player,
+        ClientPacket::Hello(HelloPacket {
+            protocol_version: PROTOCOL_VERSION,
+            client_name: None,
+            encoding: Encoding::Text,
+        }),
+        Encoding::Text,
+    )
+    .await;
+    let welcome = player.receive_text().await;
+    assert!(welcome.starts_with("[welcome] "));
+}
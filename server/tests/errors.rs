@@ -0,0 +1,93 @@
+use axum_test::TestWebSocket;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::common::{
+    connection::start_game_with_colors,
+    test_server,
+    ws::{assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Error {
+    code: String,
+    message: String,
+    details: Option<Value>,
+    packet: Option<String>,
+}
+
+async fn receive_error(connection: &mut TestWebSocket) -> Error {
+    assert_receive_message::<Error>(connection, "error")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn unparsable_packets_have_codes() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    player.send_text("[fooBar] {}").await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "unknown_packet");
+    assert_eq!(error.message, "unknown packet");
+    assert_eq!(error.packet.as_deref(), Some("fooBar"));
+
+    player.send_text("[joinGame] nope").await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "invalid_packet");
+    assert_eq!(error.packet.as_deref(), Some("joinGame"));
+    assert_eq!(error.details, None);
+}
+
+#[tokio::test]
+async fn service_errors_have_codes_and_details() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(&mut player, "startGame", None).await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "not_in_lobby");
+    assert_eq!(error.packet.as_deref(), Some("startGame"));
+
+    send_message(
+        &mut player,
+        "createGame",
+        Some(json!({ "number_of_detectives": 5 })),
+    )
+    .await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "invalid_number_of_detectives");
+    assert_eq!(error.details, Some(json!({ "max": 4 })));
+    assert_eq!(error.packet.as_deref(), Some("createGame"));
+}
+
+#[tokio::test]
+async fn invalid_moves_have_reasons() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
+
+    send_message(
+        &mut game.mister_x,
+        "moveMisterX",
+        Some(json!([{ "station_id": 106, "transport_type": "taxi" }])),
+    )
+    .await;
+    let error = receive_error(&mut game.mister_x).await;
+    assert_eq!(error.code, "invalid_move");
+    assert_eq!(error.message, "invalid move");
+    assert_eq!(error.details, Some(json!({ "reason": "no_connection" })));
+    assert_eq!(error.packet.as_deref(), Some("moveMisterX"));
+
+    send_message(
+        &mut game.detective,
+        "moveDetective",
+        Some(json!({ "color": colors[0], "station_id": 110, "transport_type": "taxi" })),
+    )
+    .await;
+    let error = receive_error(&mut game.detective).await;
+    assert_eq!(error.code, "not_your_turn");
+    assert_eq!(error.packet.as_deref(), Some("moveDetective"));
+}
@@ -0,0 +1,138 @@
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::common::{
+    connection::start_game_with_colors,
+    test_server,
+    ws::{assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Welcome {
+    protocol_version: u32,
+    server_protocol_version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+#[tokio::test]
+async fn welcomes_clients_with_the_common_version() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player,
+        "hello",
+        Some(json!({ "protocol_version": 2, "client_name": "test" })),
+    )
+    .await;
+    let welcome = assert_receive_message::<Welcome>(&mut player, "welcome")
+        .await
+        .unwrap();
+    assert_eq!(welcome.protocol_version, 2);
+    assert_eq!(welcome.server_protocol_version, 3);
+
+    send_message(&mut player, "hello", Some(json!({ "protocol_version": 9 }))).await;
+    let welcome = assert_receive_message::<Welcome>(&mut player, "welcome")
+        .await
+        .unwrap();
+    assert_eq!(welcome.protocol_version, 3);
+}
+
+#[tokio::test]
+async fn rejects_unsupported_versions() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(&mut player, "hello", Some(json!({ "protocol_version": 0 }))).await;
+    let error = assert_receive_message::<Value>(&mut player, "error")
+        .await
+        .unwrap();
+    assert_eq!(error["code"], "unsupported_protocol_version");
+    assert_eq!(error["details"], json!({ "min": 1, "max": 3 }));
+}
+
+#[tokio::test]
+async fn adapts_packets_of_old_clients() {
+    let (server, _dir) = test_server();
+    let (mut game, _) = start_game_with_colors(&server).await;
+
+    send_message(
+        &mut game.mister_x,
+        "hello",
+        Some(json!({ "protocol_version": 1 })),
+    )
+    .await;
+    let welcome = assert_receive_message::<Welcome>(&mut game.mister_x, "welcome")
+        .await
+        .unwrap();
+    assert_eq!(welcome.protocol_version, 1);
+
+    send_message(
+        &mut game.mister_x,
+        "moveMisterX",
+        Some(json!({ "station_id": 110, "transport_type": "taxi" })),
+    )
+    .await;
+    send_message(&mut game.mister_x, "submitMove", None).await;
+
+    assert_receive_message::<EndMove>(&mut game.mister_x, "endMove").await;
+    assert_receive_message::<EndMove>(&mut game.detective, "endMove").await;
+}
+
+#[tokio::test]
+async fn clients_without_hello_use_version_1() {
+    let (server, _dir) = test_server();
+    let (mut game, _) = start_game_with_colors(&server).await;
+
+    send_message(
+        &mut game.detective,
+        "moveMisterX",
+        Some(json!({ "station_id": 110, "transport_type": "taxi" })),
+    )
+    .await;
+    let error = assert_receive_message::<Value>(&mut game.detective, "error")
+        .await
+        .unwrap();
+    assert_eq!(error["code"], "not_your_turn");
+
+    send_message(
+        &mut game.mister_x,
+        "moveMisterX",
+        Some(json!({ "station_id": 110, "transport_type": "taxi" })),
+    )
+    .await;
+    send_message(&mut game.mister_x, "submitMove", None).await;
+
+    assert_receive_message::<EndMove>(&mut game.mister_x, "endMove").await;
+    assert_receive_message::<EndMove>(&mut game.detective, "endMove").await;
+}
+
+#[tokio::test]
+async fn reports_the_wrong_role_since_version_3() {
+    let (server, _dir) = test_server();
+    let (mut game, _) = start_game_with_colors(&server).await;
+
+    send_message(
+        &mut game.detective,
+        "hello",
+        Some(json!({ "protocol_version": 3 })),
+    )
+    .await;
+    assert_receive_message::<Welcome>(&mut game.detective, "welcome").await;
+
+    send_message(
+        &mut game.detective,
+        "moveMisterX",
+        Some(json!([{ "station_id": 110, "transport_type": "taxi" }])),
+    )
+    .await;
+    let error = assert_receive_message::<Value>(&mut game.detective, "error")
+        .await
+        .unwrap();
+    assert_eq!(error["code"], "wrong_role");
+    assert_eq!(error["packet"], "moveMisterX");
+}
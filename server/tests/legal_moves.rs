@@ -0,0 +1,155 @@
+use axum_test::{TestServer, TestWebSocket};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::common::{
+    connection::create_game,
+    data::Game,
+    test_server,
+    ws::{assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {
+    role: String,
+    session: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartMove {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+struct Players {
+    mister_x: TestWebSocket,
+    mister_x_session: String,
+    detective: TestWebSocket,
+}
+
+/// starts a game in which both players asked for legal moves
+async fn start_game_with_legal_moves(server: &TestServer) -> Players {
+    let mut player_1 = get_ws_connection(server).await;
+    let mut player_2 = get_ws_connection(server).await;
+
+    let id = create_game(&mut player_1).await;
+    for player in [&mut player_1, &mut player_2] {
+        send_message(
+            player,
+            "joinGame",
+            Some(json!({ "id": id, "legal_moves": true })),
+        )
+        .await;
+    }
+    send_message(&mut player_2, "startGame", None).await;
+
+    let started_1 = assert_receive_message::<GameStarted>(&mut player_1, "gameStarted")
+        .await
+        .unwrap();
+    let started_2 = assert_receive_message::<GameStarted>(&mut player_2, "gameStarted")
+        .await
+        .unwrap();
+
+    if started_1.role == "mister_x" {
+        Players {
+            mister_x: player_1,
+            mister_x_session: started_1.session,
+            detective: player_2,
+        }
+    } else {
+        Players {
+            mister_x: player_2,
+            mister_x_session: started_2.session,
+            detective: player_1,
+        }
+    }
+}
+
+async fn assert_start_move(player: &mut TestWebSocket, role: &str) {
+    let start_move = assert_receive_message::<StartMove>(player, "startMove")
+        .await
+        .unwrap();
+    assert_eq!(start_move.role, role);
+}
+
+#[tokio::test]
+async fn active_side_receives_legal_moves() {
+    let (server, _dir) = test_server();
+    let mut players = start_game_with_legal_moves(&server).await;
+
+    assert_start_move(&mut players.mister_x, "mister_x").await;
+    let legal_moves = assert_receive_message::<Value>(&mut players.mister_x, "legalMoves")
+        .await
+        .unwrap();
+    assert_eq!(
+        legal_moves,
+        json!({
+            "role": "mister_x",
+            "moves": [
+                { "station_id": 110, "transport_type": "taxi" },
+                { "station_id": 110, "transport_type": "hidden" },
+                { "station_id": 120, "transport_type": "hidden" },
+            ],
+        })
+    );
+    assert_receive_message::<Game>(&mut players.mister_x, "gameState").await;
+
+    assert_start_move(&mut players.detective, "mister_x").await;
+    assert_receive_message::<Game>(&mut players.detective, "gameState").await;
+
+    send_message(
+        &mut players.mister_x,
+        "moveMisterX",
+        Some(json!([{ "station_id": 110, "transport_type": "taxi" }])),
+    )
+    .await;
+    send_message(&mut players.mister_x, "submitMove", None).await;
+    assert_receive_message::<EndMove>(&mut players.mister_x, "endMove").await;
+    assert_receive_message::<EndMove>(&mut players.detective, "endMove").await;
+
+    assert_start_move(&mut players.mister_x, "detective").await;
+    assert_receive_message::<Game>(&mut players.mister_x, "gameState").await;
+
+    assert_start_move(&mut players.detective, "detective").await;
+    let legal_moves = assert_receive_message::<Value>(&mut players.detective, "legalMoves")
+        .await
+        .unwrap();
+    assert_eq!(legal_moves["role"], "detective");
+    let moves = legal_moves["moves"].as_array().unwrap();
+    assert_eq!(moves.len(), 11);
+    assert_eq!(
+        moves[0],
+        json!({ "color": "red", "station_id": 106, "transport_type": "taxi" })
+    );
+    assert!(moves.contains(
+        &json!({ "color": "yellow", "station_id": 119, "transport_type": "underground" })
+    ));
+    assert_receive_message::<Game>(&mut players.detective, "gameState").await;
+}
+
+#[tokio::test]
+async fn resumed_player_receives_legal_moves() {
+    let (server, _dir) = test_server();
+    let players = start_game_with_legal_moves(&server).await;
+    drop(players.mister_x);
+
+    let mut mister_x = get_ws_connection(&server).await;
+    send_message(
+        &mut mister_x,
+        "resumeGame",
+        Some(json!({ "session": players.mister_x_session })),
+    )
+    .await;
+
+    assert_receive_message::<GameStarted>(&mut mister_x, "gameStarted").await;
+    assert_start_move(&mut mister_x, "mister_x").await;
+    let legal_moves = assert_receive_message::<Value>(&mut mister_x, "legalMoves")
+        .await
+        .unwrap();
+    assert_eq!(legal_moves["moves"].as_array().unwrap().len(), 3);
+    assert_receive_message::<Game>(&mut mister_x, "gameState").await;
+}
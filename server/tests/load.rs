@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use tokio::time::timeout;
+
+use crate::common::{connection::start_game_with_colors, test_server};
+
+mod common;
+
+const CONCURRENT_GAMES: usize = 50;
+/// the games take about a second together, a lock held across games would exceed this
+const MAX_DURATION: Duration = Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn many_games_run_concurrently() {
+    let (server, _dir) = test_server();
+
+    let games = (0..CONCURRENT_GAMES).map(|_| async {
+        let (mut game, colors) = start_game_with_colors(&server).await;
+
+        game.double_move(&colors).await;
+        let state = game.catch_mister_x(&colors).await;
+
+        assert_eq!(state.players.len(), 2);
+    });
+
+    timeout(MAX_DURATION, join_all(games))
+        .await
+        .expect("games took too long");
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use axum_test::{TestServer, TestWebSocket};
+use serde::Deserialize;
+use serde_json::json;
+use server::Settings;
+
+use crate::common::{
+    test_server, test_server_with_settings,
+    ws::{assert_receive_error, assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameCreated {
+    id: String,
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LobbySettings {
+    number_of_detectives: usize,
+    public: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct LobbyInfo {
+    id: String,
+    code: String,
+    players: usize,
+    settings: LobbySettings,
+}
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {}
+
+async fn create_game(socket: &mut TestWebSocket, public: bool) -> GameCreated {
+    send_message(
+        socket,
+        "createGame",
+        Some(json!({ "number_of_detectives": 4, "public": public })),
+    )
+    .await;
+
+    assert_receive_message::<GameCreated>(socket, "game")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn creates_short_code() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    let game = create_game(&mut player, false).await;
+
+    assert_eq!(game.code.len(), 6);
+    assert!(game.code.chars().all(|char| char.is_ascii_uppercase()));
+}
+
+#[tokio::test]
+async fn can_join_by_code() {
+    let (server, _dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    let game = create_game(&mut player_1, false).await;
+
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game.code }))).await;
+    send_message(
+        &mut player_2,
+        "joinGame",
+        Some(json!({ "id": game.code.to_lowercase() })),
+    )
+    .await;
+
+    send_message(&mut player_2, "startGame", None).await;
+
+    assert_receive_message::<GameStarted>(&mut player_1, "gameStarted").await;
+    assert_receive_message::<GameStarted>(&mut player_2, "gameStarted").await;
+}
+
+#[tokio::test]
+async fn can_not_join_unknown_code() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(&mut player, "joinGame", Some(json!({ "id": "ABCDEF" }))).await;
+
+    assert_receive_error(&mut player, "unknown lobby").await;
+}
+
+#[tokio::test]
+async fn lists_public_lobbies() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    let public = create_game(&mut player, true).await;
+    create_game(&mut player, false).await;
+
+    send_message(&mut player, "joinGame", Some(json!({ "id": public.id }))).await;
+
+    let response = server.get("/game/lobbies").await;
+    response.assert_status_ok();
+    let lobbies = response.json::<Vec<LobbyInfo>>();
+
+    assert_eq!(lobbies.len(), 1);
+    assert_eq!(lobbies[0].id, public.id);
+    assert_eq!(lobbies[0].code, public.code);
+    assert_eq!(lobbies[0].players, 1);
+    assert_eq!(lobbies[0].settings.number_of_detectives, 4);
+    assert!(lobbies[0].settings.public);
+}
+
+#[tokio::test]
+async fn does_not_list_full_lobbies() {
+    let (server, _dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player_1,
+        "createGame",
+        Some(json!({ "number_of_detectives": 1, "public": true })),
+    )
+    .await;
+    let game = assert_receive_message::<GameCreated>(&mut player_1, "game")
+        .await
+        .unwrap();
+
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game.id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game.id }))).await;
+    // the second join is rejected only after the first one went through
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game.id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game.id }))).await;
+    assert_receive_error(&mut player_1, "game already joined").await;
+    assert_receive_error(&mut player_2, "game already joined").await;
+
+    let response = server.get("/game/lobbies").await;
+    response.assert_status_ok();
+    assert!(response.json::<Vec<LobbyInfo>>().is_empty());
+}
+
+#[tokio::test]
+async fn does_not_list_started_lobbies() {
+    let (server, _dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    let game = create_game(&mut player_1, true).await;
+
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game.id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game.id }))).await;
+    send_message(&mut player_2, "startGame", None).await;
+
+    assert_receive_message::<GameStarted>(&mut player_1, "gameStarted").await;
+
+    let response = server.get("/game/lobbies").await;
+    response.assert_status_ok();
+    assert!(response.json::<Vec<LobbyInfo>>().is_empty());
+}
+
+/// lobbies are removed once the socket of their last player is closed
+async fn wait_for_no_lobbies(server: &TestServer) {
+    for _ in 0..50 {
+        if server
+            .get("/game/lobbies")
+            .await
+            .json::<Vec<LobbyInfo>>()
+            .is_empty()
+        {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("lobby was not removed");
+}
+
+#[tokio::test]
+async fn removes_lobbies_once_every_player_left() {
+    let mut settings = Settings::default();
+    settings.limits.max_lobbies = 1;
+    let (server, _dir) = test_server_with_settings(settings);
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    let game = create_game(&mut player_1, true).await;
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game.id }))).await;
+    send_message(&mut player_1, "addBot", Some(json!({ "name": "random" }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game.id }))).await;
+    // answered once both joins are handled
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game.id }))).await;
+    assert_receive_error(&mut player_2, "game already joined").await;
+
+    player_1.close().await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let lobbies = server.get("/game/lobbies").await.json::<Vec<LobbyInfo>>();
+    assert_eq!(lobbies.len(), 1);
+
+    // the bot does not keep the lobby open
+    player_2.close().await;
+    wait_for_no_lobbies(&server).await;
+
+    let mut player_3 = get_ws_connection(&server).await;
+    create_game(&mut player_3, true).await;
+}
+
+#[tokio::test]
+async fn removes_lobbies_nobody_joined() {
+    let mut settings = Settings::default();
+    settings.limits.max_lobbies = 1;
+    let (server, _dir) = test_server_with_settings(settings);
+
+    let mut player_1 = get_ws_connection(&server).await;
+    create_game(&mut player_1, true).await;
+    player_1.close().await;
+    wait_for_no_lobbies(&server).await;
+
+    let mut player_2 = get_ws_connection(&server).await;
+    create_game(&mut player_2, true).await;
+}
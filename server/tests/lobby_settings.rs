@@ -0,0 +1,109 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{
+    connection::create_game_with_settings,
+    test_prod_server, test_server,
+    ws::{assert_receive_error, assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameCreated {}
+
+#[tokio::test]
+async fn can_not_create_game_without_detectives() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player,
+        "createGame",
+        Some(json!({ "number_of_detectives": 0 })),
+    )
+    .await;
+
+    assert_receive_error(
+        &mut player,
+        "invalid number of detectives, must be between 1 and 4",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn can_not_create_game_with_more_detectives_than_start_stations() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player,
+        "createGame",
+        Some(json!({ "number_of_detectives": 5 })),
+    )
+    .await;
+
+    assert_receive_error(
+        &mut player,
+        "invalid number of detectives, must be between 1 and 4",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn can_not_create_game_with_more_detectives_than_colors() {
+    let (server, _dir) = test_prod_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player,
+        "createGame",
+        Some(json!({ "number_of_detectives": 5 })),
+    )
+    .await;
+    assert_receive_message::<GameCreated>(&mut player, "game").await;
+
+    send_message(
+        &mut player,
+        "createGame",
+        Some(json!({ "number_of_detectives": 6 })),
+    )
+    .await;
+    assert_receive_error(
+        &mut player,
+        "invalid number of detectives, must be between 1 and 5",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn can_not_join_full_lobby() {
+    let (server, _dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+    let mut player_3 = get_ws_connection(&server).await;
+
+    let game_id =
+        create_game_with_settings(&mut player_1, json!({ "number_of_detectives": 1 })).await;
+
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": game_id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": game_id }))).await;
+    send_message(&mut player_3, "joinGame", Some(json!({ "id": game_id }))).await;
+
+    assert_receive_error(&mut player_3, "lobby is full").await;
+}
+
+#[tokio::test]
+async fn can_not_add_bot_to_full_lobby() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    let game_id =
+        create_game_with_settings(&mut player, json!({ "number_of_detectives": 1 })).await;
+
+    send_message(&mut player, "joinGame", Some(json!({ "id": game_id }))).await;
+    send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+    send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+
+    assert_receive_error(&mut player, "lobby is full").await;
+}
@@ -0,0 +1,294 @@
+use std::{fs, sync::Arc};
+
+use axum_test::http::StatusCode;
+use game::{
+    data::StationType,
+    map::{MapError, MapFile, MapProblem, Rules, StartPools},
+};
+use serde::Deserialize;
+use serde_json::json;
+use server::services::data::{MapRegistry, service::DataService};
+use tempfile::TempDir;
+
+use crate::common::{
+    connection::start_game_with_settings,
+    data::Game,
+    test_server_with_maps,
+    ws::{assert_receive_error, assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Station {
+    id: u8,
+}
+
+const LONDON_DETECTIVE_STATIONS: &[u32] = &[
+    13, 26, 29, 91, 117, 34, 50, 53, 94, 103, 112, 123, 138, 141, 155, 174,
+];
+
+#[tokio::test]
+async fn lists_all_maps() {
+    let (server, _dir) = test_server_with_maps();
+
+    let maps = server.get("/map").await.json::<Vec<String>>();
+    assert_eq!(maps, vec!["london", "london-full"]);
+}
+
+#[tokio::test]
+async fn map_routes_select_map() {
+    let (server, _dir) = test_server_with_maps();
+
+    let default = server.get("/map/stations").await.json::<Vec<Station>>();
+    assert_eq!(default.len(), 25);
+
+    let full = server
+        .get("/map/stations")
+        .add_query_param("map", "london-full")
+        .await
+        .json::<Vec<Station>>();
+    assert_eq!(full.len(), 199);
+    assert_eq!(full[0].id, 1);
+
+    server
+        .get("/map/rounds")
+        .add_query_param("map", "paris")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn lobby_rejects_unknown_map() {
+    let (server, _dir) = test_server_with_maps();
+    let mut socket = get_ws_connection(&server).await;
+
+    send_message(
+        &mut socket,
+        "createGame",
+        Some(json!({ "number_of_detectives": 4, "map": "paris" })),
+    )
+    .await;
+
+    assert_receive_error(&mut socket, "unknown map").await;
+}
+
+#[tokio::test]
+async fn game_is_played_on_lobby_map() {
+    let (server, _dir) = test_server_with_maps();
+
+    let mut game = start_game_with_settings(
+        &server,
+        json!({ "number_of_detectives": 4, "map": "london-full" }),
+    )
+    .await;
+    game.receive_start_move_message("mister_x").await;
+
+    let state = assert_receive_message::<Game>(&mut game.detective, "gameState")
+        .await
+        .unwrap();
+    assert_eq!(state.players.len(), 4);
+    assert!(
+        state
+            .players
+            .iter()
+            .all(|player| LONDON_DETECTIVE_STATIONS.contains(&player.station_id))
+    );
+}
+
+const TINY_MAP: &str = r#"version = 1
+name = "Tiny"
+
+stations = [
+    { id = 1, pos_x = 10, pos_y = 10, types = ["taxi"], name = "Harbour" },
+    { id = 2, pos_x = 20, pos_y = 20, types = ["taxi"] },
+]
+
+connections = [
+    { from = 1, to = 2, mode = "taxi" },
+]
+
+[rules]
+rounds = 3
+reveal_rounds = [2]
+colors = ["red"]
+
+[start]
+detectives = [1]
+mister_x = [2]
+"#;
+
+#[test]
+fn loads_maps_from_directory() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("tiny.toml"), TINY_MAP).unwrap();
+    fs::write(dir.path().join("notes.txt"), "not a map").unwrap();
+
+    let mut maps = MapRegistry::new(Arc::new(DataService::default()));
+    maps.load_dir(dir.path()).unwrap();
+    assert_eq!(maps.ids(), vec!["london", "tiny"]);
+
+    let tiny = maps.get("tiny").unwrap();
+    let stations = tiny.get_all_stations();
+    assert_eq!(stations.len(), 2);
+    assert_eq!(stations[0].name.as_deref(), Some("Harbour"));
+    assert_eq!(tiny.get_all_rounds().len(), 3);
+    assert_eq!(tiny.get_colors(), vec!["red"]);
+    assert_eq!(tiny.get_random_mister_x_station(), 2);
+}
+
+#[test]
+fn invalid_map_file_is_reported_with_location() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("tiny.toml");
+    fs::write(
+        &path,
+        TINY_MAP.replace(r#"mode = "taxi""#, r#"mode = "boat""#),
+    )
+    .unwrap();
+
+    let err = MapRegistry::new(Arc::new(DataService::default()))
+        .load_dir(dir.path())
+        .unwrap_err();
+    assert!(err.to_string().starts_with(&format!(
+        "{}: line 10, column 32: unknown variant `boat`",
+        path.display()
+    )));
+}
+
+#[test]
+fn map_file_errors_name_the_field() {
+    assert_eq!(
+        MapFile::parse(&TINY_MAP.replace("version = 1", "version = 2")).unwrap_err(),
+        MapError::UnsupportedVersion(2)
+    );
+    assert_eq!(
+        MapFile::parse(&TINY_MAP.replace("version = 1\n", "")).unwrap_err(),
+        MapError::MissingVersion
+    );
+    assert_eq!(
+        MapFile::parse(&TINY_MAP.replace("reveal_rounds = [2]", "reveal_rounds = [2, 4]"))
+            .unwrap_err()
+            .to_string(),
+        "rules.reveal_rounds[1]: round 4 does not exist"
+    );
+    assert_eq!(
+        MapFile::parse(&TINY_MAP.replace("mister_x = [2]", "mister_x = []"))
+            .unwrap_err()
+            .to_string(),
+        "start.mister_x: must not be empty"
+    );
+    assert!(matches!(
+        MapFile::parse(&TINY_MAP.replace("pos_y = 20,", "")).unwrap_err(),
+        MapError::Syntax { line: 6, .. }
+    ));
+}
+
+#[test]
+fn map_problems_are_found() {
+    let map = MapFile::parse_unchecked(
+        &TINY_MAP
+            .replace(
+                r#"{ from = 1, to = 2, mode = "taxi" },"#,
+                r#"{ from = 1, to = 2, mode = "taxi" }, { from = 2, to = 1, mode = "taxi" }, { from = 2, to = 3, mode = "bus" },"#,
+            )
+            .replace("mister_x = [2]", "mister_x = [2, 4]"),
+    )
+    .unwrap();
+
+    assert_eq!(
+        map.check(),
+        vec![
+            MapProblem::DuplicateConnection {
+                from: 2,
+                to: 1,
+                mode: StationType::Taxi
+            },
+            MapProblem::DanglingConnection {
+                from: 2,
+                to: 3,
+                missing: 3
+            },
+            MapProblem::UnknownStartStation {
+                pool: "mister x",
+                station: 4
+            },
+        ]
+    );
+}
+
+#[test]
+fn unreachable_stations_are_found_outside_of_the_largest_part_of_the_map() {
+    let map = MapFile::parse_unchecked(&TINY_MAP.replace(
+        "stations = [\n",
+        "stations = [\n    { id = 3, pos_x = 30, pos_y = 30, types = [] },\n",
+    ))
+    .unwrap();
+
+    assert_eq!(map.check(), vec![MapProblem::UnreachableStation(3)]);
+}
+
+#[test]
+fn maps_with_problems_are_not_loaded() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join("tiny.toml"),
+        TINY_MAP.replace(r#"types = ["taxi"] }"#, r#"types = ["taxi", "bus"] }"#),
+    )
+    .unwrap();
+
+    let err = MapRegistry::new(Arc::new(DataService::default()))
+        .load_dir(dir.path())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .ends_with("station 2 is a Bus station but has no Bus connection")
+    );
+}
+
+#[test]
+fn builtin_map_has_no_problems() {
+    let map = MapFile::parse_unchecked(include_str!("../data/london.toml")).unwrap();
+
+    assert_eq!(map.check(), vec![]);
+}
+
+#[test]
+fn converts_text_format() {
+    let map = MapFile::from_txt(
+        "1 10 10 taxi,bus\n2 20 20 taxi,bus\n",
+        "1 2 taxi\n1 2 bus\n",
+        Rules::default(),
+        StartPools {
+            detectives: vec![1],
+            mister_x: vec![2],
+        },
+    )
+    .unwrap();
+
+    let parsed = MapFile::parse(&map.to_toml()).unwrap();
+    assert_eq!(parsed.stations.len(), 2);
+    assert_eq!(parsed.connections.len(), 2);
+    assert_eq!(parsed.start.mister_x, vec![2]);
+
+    assert_eq!(
+        MapFile::from_txt(
+            "1 10 10 taxi\n2 20 twenty taxi\n",
+            "",
+            Rules::default(),
+            map.start
+        )
+        .unwrap_err()
+        .to_string(),
+        "line 2 of stations.txt: invalid y position"
+    );
+}
+
+#[test]
+fn missing_maps_directory_is_ignored() {
+    let dir = TempDir::new().unwrap();
+
+    let mut maps = MapRegistry::new(Arc::new(DataService::default()));
+    maps.load_dir(&dir.path().join("maps")).unwrap();
+    assert_eq!(maps.ids(), vec!["london"]);
+}
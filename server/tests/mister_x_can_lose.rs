@@ -15,8 +15,8 @@ struct EndMove;
 
 #[tokio::test]
 async fn can_lose_after_detective_move() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     game.full_move_mister_x(110).await;
 
@@ -37,8 +37,8 @@ async fn can_lose_after_detective_move() {
 
 #[tokio::test]
 async fn can_lose_after_mister_x_move() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     send_message(
         &mut game.mister_x,
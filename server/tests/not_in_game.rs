@@ -42,8 +42,8 @@ struct EndMove;
 
 #[tokio::test]
 async fn sends_error_after_finished_game() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     game.full_move_mister_x(110).await;
 
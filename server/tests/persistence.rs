@@ -0,0 +1,331 @@
+use std::time::Duration;
+
+use axum_test::{TestWebSocket, WsMessage};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::time::timeout;
+
+use crate::common::{
+    connection::{GameConnection, create_game_with_settings},
+    data::Game,
+    restart_test_server, test_server, test_server_with_maps,
+    ws::{
+        assert_receive_error, assert_receive_message, get_ws_connection, receive_any_message,
+        send_message,
+    },
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {
+    role: String,
+    session: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartMove {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+#[derive(Debug, Deserialize)]
+struct Ack {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LobbyInfo {
+    id: String,
+    players: usize,
+}
+
+async fn resume(socket: &mut TestWebSocket, session: &str, expected_role: &str) -> Game {
+    send_message(socket, "resumeGame", Some(json!({ "session": session }))).await;
+
+    let started = assert_receive_message::<GameStarted>(socket, "gameStarted")
+        .await
+        .unwrap();
+    assert_eq!(started.role, expected_role);
+    assert_eq!(started.session, session);
+
+    let start_move = assert_receive_message::<StartMove>(socket, "startMove")
+        .await
+        .unwrap();
+    assert_eq!(start_move.role, "detective");
+
+    assert_receive_message::<Game>(socket, "gameState")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn game_continues_after_restart() {
+    let (server, dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    let id = create_game_with_settings(&mut player_1, json!({ "number_of_detectives": 4 })).await;
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": id }))).await;
+    send_message(&mut player_1, "startGame", None).await;
+
+    let started_1 = assert_receive_message::<GameStarted>(&mut player_1, "gameStarted")
+        .await
+        .unwrap();
+    let started_2 = assert_receive_message::<GameStarted>(&mut player_2, "gameStarted")
+        .await
+        .unwrap();
+    assert_ne!(started_1.session, started_2.session);
+
+    let (mut game, mister_x_session, detective_session) = if started_1.role == "mister_x" {
+        let game = GameConnection {
+            id,
+            mister_x: player_1,
+            detective: player_2,
+        };
+        (game, started_1.session, started_2.session)
+    } else {
+        let game = GameConnection {
+            id,
+            mister_x: player_2,
+            detective: player_1,
+        };
+        (game, started_2.session, started_1.session)
+    };
+
+    game.receive_start_move_message("mister_x").await;
+    assert_receive_message::<Game>(&mut game.mister_x, "gameState").await;
+    let state = assert_receive_message::<Game>(&mut game.detective, "gameState")
+        .await
+        .unwrap();
+    let colors: Vec<_> = state
+        .players
+        .into_iter()
+        .map(|player| player.color)
+        .collect();
+
+    let seq = game.full_move_mister_x(110).await.seq;
+
+    // answered once the move was stored, as it carries an id
+    game.mister_x.send_text("[submitMove#stored]").await;
+    assert_receive_error(&mut game.mister_x, "not your turn").await;
+
+    let server = restart_test_server(&dir);
+    let mut mister_x = get_ws_connection(&server).await;
+    let mut detective = get_ws_connection(&server).await;
+
+    let state = resume(&mut mister_x, &mister_x_session, "mister_x").await;
+    assert_eq!(state.mister_x.station_id, Some(110));
+    let state = resume(&mut detective, &detective_session, "detective").await;
+    assert_eq!(state.players.len(), 4);
+    // seq continues where the game stopped
+    assert_eq!(state.seq, seq);
+
+    let mut game = GameConnection {
+        id: game.id,
+        mister_x,
+        detective,
+    };
+
+    let state = game
+        .full_move_detectives(
+            &colors,
+            &[106, 107, 108, 109],
+            &["taxi", "bus", "bus", "taxi"],
+        )
+        .await;
+    assert!(state.seq > seq);
+}
+
+#[tokio::test]
+async fn bot_continues_after_restart() {
+    let (server, dir) = test_server();
+
+    // Mister X is chosen randomly, so lobbies are created until the bot is Mister X
+    let (mut player, session) = loop {
+        let mut player = get_ws_connection(&server).await;
+        let id = create_game_with_settings(&mut player, json!({ "number_of_detectives": 1 })).await;
+        send_message(&mut player, "joinGame", Some(json!({ "id": id }))).await;
+        send_message(&mut player, "addBot", Some(json!({ "name": "random" }))).await;
+        send_message(&mut player, "startGame", None).await;
+
+        let started = assert_receive_message::<GameStarted>(&mut player, "gameStarted")
+            .await
+            .unwrap();
+        if started.role == "detective" {
+            break (player, started.session);
+        }
+    };
+
+    assert_receive_message::<StartMove>(&mut player, "startMove").await;
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+    assert_receive_message::<EndMove>(&mut player, "endMove").await;
+    assert_receive_message::<StartMove>(&mut player, "startMove").await;
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+
+    // answered once the move of the bot was stored, as it carries an id
+    player.send_text("[resync#stored]").await;
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+    let ack = assert_receive_message::<Ack>(&mut player, "ack")
+        .await
+        .unwrap();
+    assert_eq!(ack.id, "stored");
+
+    let server = restart_test_server(&dir);
+    let mut player = get_ws_connection(&server).await;
+
+    let state = resume(&mut player, &session, "detective").await;
+    assert_eq!(state.mister_x.moves.len(), 1);
+
+    send_message(
+        &mut player,
+        "moveDetective",
+        Some(
+            json!({ "color": state.players[0].color, "station_id": 106, "transport_type": "taxi" }),
+        ),
+    )
+    .await;
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+    send_message(&mut player, "submitMove", None).await;
+
+    assert_receive_message::<EndMove>(&mut player, "endMove").await;
+    let start_move = assert_receive_message::<StartMove>(&mut player, "startMove")
+        .await
+        .unwrap();
+    assert_eq!(start_move.role, "mister_x");
+    assert_receive_message::<Game>(&mut player, "gameState").await;
+
+    // the restored bot plays its turn, which may already catch it
+    assert_receive_message::<EndMove>(&mut player, "endMove").await;
+    let (name, _) = receive_any_message(&mut player).await;
+    assert!(name == "startMove" || name == "gameEnded");
+}
+
+#[tokio::test]
+async fn resuming_closes_previous_connection() {
+    let (server, _dir) = test_server();
+    let mut player_1 = get_ws_connection(&server).await;
+    let mut player_2 = get_ws_connection(&server).await;
+
+    let id = create_game_with_settings(&mut player_1, json!({ "number_of_detectives": 4 })).await;
+    send_message(&mut player_1, "joinGame", Some(json!({ "id": id }))).await;
+    send_message(&mut player_2, "joinGame", Some(json!({ "id": id }))).await;
+    send_message(&mut player_1, "startGame", None).await;
+
+    let started_1 = assert_receive_message::<GameStarted>(&mut player_1, "gameStarted")
+        .await
+        .unwrap();
+    let started_2 = assert_receive_message::<GameStarted>(&mut player_2, "gameStarted")
+        .await
+        .unwrap();
+    let (mut mister_x, mut detective, session) = if started_1.role == "mister_x" {
+        (player_1, player_2, started_1.session)
+    } else {
+        (player_2, player_1, started_2.session)
+    };
+    for player in [&mut mister_x, &mut detective] {
+        assert_receive_message::<StartMove>(player, "startMove").await;
+        assert_receive_message::<Game>(player, "gameState").await;
+    }
+
+    let mut resumed = get_ws_connection(&server).await;
+    send_message(
+        &mut resumed,
+        "resumeGame",
+        Some(json!({ "session": session })),
+    )
+    .await;
+    assert_receive_message::<GameStarted>(&mut resumed, "gameStarted").await;
+    assert_receive_message::<StartMove>(&mut resumed, "startMove").await;
+    assert_receive_message::<Game>(&mut resumed, "gameState").await;
+
+    let message = timeout(Duration::from_millis(500), mister_x.receive_message())
+        .await
+        .unwrap();
+    let WsMessage::Close(Some(frame)) = message else {
+        panic!("expected the previous connection to be closed");
+    };
+    assert_eq!(frame.reason, "session resumed on another connection");
+    drop(mister_x);
+
+    send_message(
+        &mut resumed,
+        "moveMisterX",
+        Some(json!([{ "station_id": 110, "transport_type": "taxi" }])),
+    )
+    .await;
+    send_message(&mut resumed, "submitMove", None).await;
+
+    assert_receive_message::<EndMove>(&mut resumed, "endMove").await;
+    assert_receive_message::<EndMove>(&mut detective, "endMove").await;
+}
+
+#[tokio::test]
+async fn lobby_survives_restart() {
+    let (server, dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    let id = create_game_with_settings(
+        &mut player,
+        json!({ "number_of_detectives": 4, "public": true }),
+    )
+    .await;
+    send_message(&mut player, "joinGame", Some(json!({ "id": id }))).await;
+    // answered once the join was stored, as it carries an id
+    player
+        .send_text(format!("[joinGame#stored] {}", json!({ "id": id })))
+        .await;
+    assert_receive_error(&mut player, "game already joined").await;
+
+    let server = restart_test_server(&dir);
+
+    let lobbies = server.get("/game/lobbies").await.json::<Vec<LobbyInfo>>();
+    assert_eq!(lobbies.len(), 1);
+    assert_eq!(lobbies[0].id, id);
+    assert_eq!(lobbies[0].players, 1);
+
+    let mut player = get_ws_connection(&server).await;
+    send_message(&mut player, "joinGame", Some(json!({ "id": id }))).await;
+    send_message(&mut player, "startGame", None).await;
+    assert_receive_message::<GameStarted>(&mut player, "gameStarted").await;
+}
+
+#[tokio::test]
+async fn lobby_of_missing_map_is_dropped_on_restart() {
+    let (server, dir) = test_server_with_maps();
+    let mut player = get_ws_connection(&server).await;
+
+    let id = create_game_with_settings(
+        &mut player,
+        json!({ "number_of_detectives": 4, "public": true, "map": "london-full" }),
+    )
+    .await;
+    player
+        .send_text(format!("[joinGame#stored] {}", json!({ "id": id })))
+        .await;
+    assert_receive_message::<Ack>(&mut player, "ack").await;
+
+    // the restarted server only knows the test map
+    let server = restart_test_server(&dir);
+
+    let lobbies = server.get("/game/lobbies").await.json::<Vec<LobbyInfo>>();
+    assert!(lobbies.is_empty());
+}
+
+#[tokio::test]
+async fn unknown_session_is_rejected() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(
+        &mut player,
+        "resumeGame",
+        Some(json!({ "session": "00000000-0000-0000-0000-000000000000" })),
+    )
+    .await;
+
+    assert_receive_error(&mut player, "unknown session").await;
+}
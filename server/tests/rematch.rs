@@ -0,0 +1,95 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{
+    connection::{GameConnection, start_game_with_colors},
+    data::{Game, LobbyState},
+    test_server,
+    ws::{assert_receive_error, assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {
+    role: String,
+}
+
+impl GameConnection {
+    async fn rematch(mut self) -> (GameConnection, Vec<String>) {
+        send_message(&mut self.mister_x, "rematch", None).await;
+        assert_receive_message::<LobbyState>(&mut self.mister_x, "lobbyState").await;
+        assert_receive_message::<LobbyState>(&mut self.detective, "lobbyState").await;
+
+        send_message(&mut self.detective, "rematch", None).await;
+        let state = assert_receive_message::<LobbyState>(&mut self.mister_x, "lobbyState")
+            .await
+            .unwrap();
+        assert!(state.players.iter().all(|player| player.accepted_rematch));
+        assert_receive_message::<LobbyState>(&mut self.detective, "lobbyState").await;
+
+        let message =
+            assert_receive_message::<GameStarted>(&mut self.mister_x, "gameStarted").await;
+        assert_eq!(message.unwrap().role, "detective");
+        let message =
+            assert_receive_message::<GameStarted>(&mut self.detective, "gameStarted").await;
+        assert_eq!(message.unwrap().role, "mister_x");
+
+        let mut game = GameConnection {
+            id: self.id,
+            mister_x: self.detective,
+            detective: self.mister_x,
+        };
+
+        game.receive_start_move_message("mister_x").await;
+        assert_receive_message::<Game>(&mut game.mister_x, "gameState").await;
+        let state = assert_receive_message::<Game>(&mut game.detective, "gameState")
+            .await
+            .unwrap();
+        let colors = state
+            .players
+            .into_iter()
+            .map(|player| player.color)
+            .collect();
+
+        (game, colors)
+    }
+}
+
+#[tokio::test]
+async fn rematch_swaps_roles_and_keeps_score() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
+
+    let state = game.catch_mister_x(&colors).await;
+    let mut scores: Vec<_> = state.players.iter().map(|player| player.score).collect();
+    scores.sort();
+    assert_eq!(scores, vec![0, 1]);
+    assert!(state.players.iter().all(|player| !player.accepted_rematch));
+
+    let (mut game, colors) = game.rematch().await;
+
+    let state = game.catch_mister_x(&colors).await;
+    assert!(state.players.iter().all(|player| player.score == 1));
+}
+
+#[tokio::test]
+async fn can_not_rematch_without_finished_game() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    send_message(&mut player, "rematch", None).await;
+    assert_receive_error(&mut player, "no rematch available").await;
+}
+
+#[tokio::test]
+async fn can_not_join_rematch_lobby() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
+
+    let state = game.catch_mister_x(&colors).await;
+
+    let mut player = get_ws_connection(&server).await;
+    send_message(&mut player, "joinGame", Some(json!({ "id": state.id }))).await;
+    assert_receive_error(&mut player, "lobby is reserved for a rematch").await;
+}
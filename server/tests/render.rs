@@ -0,0 +1,146 @@
+use axum_test::{TestServer, http::StatusCode};
+use game::{
+    data::{Connection, Station, StationType},
+    map::render::{self, Overlay},
+};
+use uuid::Uuid;
+
+use crate::common::{
+    connection::{start_game, start_game_with_colors},
+    test_server, test_server_with_maps,
+};
+
+mod common;
+
+/// attributes of a station in the rendered graph
+fn dot_station(dot: &str, station: u8) -> &str {
+    dot.lines()
+        .find(|line| line.trim_start().starts_with(&format!("{station} [")))
+        .unwrap()
+}
+
+async fn get_dot(server: &TestServer, query: &[(&str, &str)]) -> String {
+    let mut request = server.get("/map/dot");
+    for (name, value) in query {
+        request = request.add_query_param(name, value);
+    }
+
+    request.await.text()
+}
+
+#[tokio::test]
+async fn renders_map_as_svg() {
+    let (server, _dir) = test_server();
+
+    let response = server.get("/map/svg").await;
+    response.assert_status_ok();
+    response.assert_header("content-type", "image/svg+xml");
+
+    let svg = response.text();
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<line").count(), 15);
+    assert_eq!(svg.matches("<title>").count(), 25);
+    assert!(svg.contains(r##"fill="#F2C94C""##));
+}
+
+#[tokio::test]
+async fn renders_map_as_dot() {
+    let (server, _dir) = test_server_with_maps();
+
+    let response = server
+        .get("/map/dot")
+        .add_query_param("map", "london-full")
+        .await;
+    response.assert_status_ok();
+    response.assert_header("content-type", "text/vnd.graphviz");
+
+    let dot = response.text();
+    assert!(dot.starts_with("graph map {"));
+    assert_eq!(
+        dot_station(&dot, 1),
+        r##"    1 [pos="190,40", fillcolor="#2F80ED", tooltip="1"];"##
+    );
+    assert!(dot.contains(r##"    1 -- 8 [color="#F2C94C", penwidth=2];"##));
+}
+
+#[tokio::test]
+async fn game_overlay_hides_mister_x() {
+    let (server, _dir) = test_server();
+    let game = start_game(&server).await;
+
+    let dot = get_dot(&server, &[("game", &game.id)]).await;
+    assert!(dot_station(&dot, 100).contains(r#"xlabel="red""#));
+    assert!(dot_station(&dot, 103).contains(r#"xlabel="yellow""#));
+    assert!(!dot.contains("Mister X"));
+}
+
+#[tokio::test]
+async fn replay_overlay_shows_positions_at_step() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
+    game.catch_mister_x(&colors).await;
+
+    let start = get_dot(&server, &[("replay", &game.id), ("step", "0")]).await;
+    assert!(dot_station(&start, 104).contains(r#"xlabel="Mister X""#));
+    assert!(dot_station(&start, 100).contains(r#"xlabel="red""#));
+
+    let end = get_dot(&server, &[("replay", &game.id)]).await;
+    assert!(dot_station(&end, 110).contains(r#"xlabel="red, Mister X""#));
+    assert!(!dot_station(&end, 104).contains("xlabel"));
+}
+
+#[tokio::test]
+async fn invalid_overlays_are_rejected() {
+    let (server, _dir) = test_server_with_maps();
+    let game = start_game(&server).await;
+    let unknown = Uuid::new_v4().to_string();
+
+    server
+        .get("/map/svg")
+        .add_query_param("game", &unknown)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/map/svg")
+        .add_query_param("replay", &unknown)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/map/svg")
+        .add_query_param("game", &game.id)
+        .add_query_param("replay", &unknown)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    server
+        .get("/map/svg")
+        .add_query_param("game", &game.id)
+        .add_query_param("map", "london-full")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn svg_escapes_names_and_skips_unknown_stations() {
+    let stations = vec![Station {
+        id: 1,
+        pos_x: 10,
+        pos_y: 10,
+        types: vec![StationType::Taxi],
+        name: Some("Tom & Jerry's <Pub>".to_string()),
+    }];
+    let connections = vec![Connection {
+        from: 1,
+        to: 2,
+        mode: StationType::Taxi,
+    }];
+    let overlay = Overlay {
+        detectives: vec![("red".to_string(), 3)],
+        mister_x: Some(1),
+    };
+
+    let svg = render::to_svg(&stations, &connections, &overlay);
+    assert!(svg.contains("<title>1 Tom &amp; Jerry's &lt;Pub&gt;</title>"));
+    assert!(!svg.contains("<line"));
+    assert!(!svg.contains(r#"stroke="red""#));
+    assert!(svg.contains("<title>Mister X</title>"));
+}
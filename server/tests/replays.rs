@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use axum_test::{TestServer, http::StatusCode};
+use futures::future::join_all;
+use game::replay::Replay;
+use serde::Deserialize;
+use server::services::replay::{
+    ReplayFilter, ReplayPage, ReplayStore, ReplayStoreError, ReplayStoreHandle,
+    filesystem::FilesystemReplayStore, memory::MemoryReplayStore, sqlite::SqliteReplayStore,
+};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+use crate::common::{
+    connection::start_game_with_colors,
+    data::Game,
+    test_server, test_server_with_replay_store,
+    ws::{assert_receive_message, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Error {
+    code: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplayInfo {
+    id: String,
+    winner: String,
+    number_of_detectives: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplayPageData {
+    total: usize,
+    page: usize,
+    replays: Vec<ReplayInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplayData {
+    winner: String,
+    actions: Vec<serde_json::Value>,
+    map: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+#[derive(Debug, Deserialize)]
+struct GameEnded {
+    winner: String,
+}
+
+/// plays a game which the detectives win and returns its id
+async fn finish_game(server: &TestServer) -> String {
+    let (mut game, colors) = start_game_with_colors(server).await;
+    game.catch_mister_x(&colors).await;
+
+    game.id
+}
+
+async fn finished_game_is_listed(replay_store: ReplayStoreHandle) {
+    let (server, _dir) = test_server_with_replay_store(replay_store);
+    let id = finish_game(&server).await;
+
+    let page = server.get("/game/replays").await.json::<ReplayPageData>();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.replays[0].id, id);
+    assert_eq!(page.replays[0].winner, "detective");
+    assert_eq!(page.replays[0].number_of_detectives, 4);
+
+    let replay = server
+        .get(&format!("/game/replays/{}", id))
+        .await
+        .json::<ReplayData>();
+    assert_eq!(replay.winner, "detective");
+    assert_eq!(replay.actions.len(), 5);
+    assert_eq!(replay.map.as_deref(), Some("london"));
+}
+
+async fn replays_can_be_filtered_and_paginated(replay_store: ReplayStoreHandle) {
+    let (server, _dir) = test_server_with_replay_store(replay_store);
+
+    join_all((0..3).map(|_| finish_game(&server))).await;
+
+    let page = server
+        .get("/game/replays")
+        .add_query_param("per_page", 2)
+        .add_query_param("page", 1)
+        .await
+        .json::<ReplayPageData>();
+    assert_eq!(page.total, 3);
+    assert_eq!(page.page, 1);
+    assert_eq!(page.replays.len(), 1);
+
+    let page = server
+        .get("/game/replays")
+        .add_query_param("per_page", 100)
+        .add_query_param("page", usize::MAX)
+        .await
+        .json::<ReplayPageData>();
+    assert_eq!(page.total, 3);
+    assert!(page.replays.is_empty());
+
+    let page = server
+        .get("/game/replays")
+        .add_query_param("winner", "mister_x")
+        .await
+        .json::<ReplayPageData>();
+    assert_eq!(page.total, 0);
+
+    let page = server
+        .get("/game/replays")
+        .add_query_param("winner", "detective")
+        .add_query_param("from", 0)
+        .add_query_param("to", u32::MAX)
+        .await
+        .json::<ReplayPageData>();
+    assert_eq!(page.total, 3);
+
+    let page = server
+        .get("/game/replays")
+        .add_query_param("from", u32::MAX)
+        .await
+        .json::<ReplayPageData>();
+    assert_eq!(page.total, 0);
+}
+
+#[tokio::test]
+async fn memory_store_lists_replays() {
+    finished_game_is_listed(Arc::new(MemoryReplayStore::default())).await;
+    replays_can_be_filtered_and_paginated(Arc::new(MemoryReplayStore::default())).await;
+}
+
+#[tokio::test]
+async fn filesystem_store_lists_replays() {
+    let dir = TempDir::new().unwrap();
+    finished_game_is_listed(Arc::new(FilesystemReplayStore::new(dir.path().join("a")))).await;
+    replays_can_be_filtered_and_paginated(Arc::new(FilesystemReplayStore::new(
+        dir.path().join("b"),
+    )))
+    .await;
+}
+
+#[tokio::test]
+async fn sqlite_store_lists_replays() {
+    finished_game_is_listed(Arc::new(SqliteReplayStore::in_memory().unwrap())).await;
+    replays_can_be_filtered_and_paginated(Arc::new(SqliteReplayStore::in_memory().unwrap())).await;
+}
+
+#[tokio::test]
+async fn filesystem_index_survives_restart() {
+    let dir = TempDir::new().unwrap();
+
+    let (server, _data) =
+        test_server_with_replay_store(Arc::new(FilesystemReplayStore::new(dir.path())));
+    let id = finish_game(&server).await;
+
+    let store = FilesystemReplayStore::new(dir.path());
+    let page = store.list(&ReplayFilter::default(), 0, 10).unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.replays[0].id.to_string(), id);
+
+    // saving a replay again replaces its index entry
+    let id = page.replays[0].id;
+    store.save(id, &store.get(&id).unwrap()).unwrap();
+    let store = FilesystemReplayStore::new(dir.path());
+    assert_eq!(
+        store.list(&ReplayFilter::default(), 0, 10).unwrap().total,
+        1
+    );
+}
+
+#[tokio::test]
+async fn sqlite_store_survives_restart() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("replays.db");
+
+    let (server, _data) =
+        test_server_with_replay_store(Arc::new(SqliteReplayStore::open(&path).unwrap()));
+    let id = finish_game(&server).await;
+
+    let store = SqliteReplayStore::open(&path).unwrap();
+    let page = store.list(&ReplayFilter::default(), 0, 10).unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.replays[0].id.to_string(), id);
+    assert!(store.get(&page.replays[0].id).is_ok());
+}
+
+#[tokio::test]
+async fn unknown_replay_is_not_found() {
+    let (server, _dir) = test_server();
+
+    server
+        .get("/game/replays/00000000-0000-0000-0000-000000000000")
+        .expect_failure()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+struct FailingReplayStore;
+
+impl ReplayStore for FailingReplayStore {
+    fn save(&self, _id: Uuid, _replay: &Replay) -> Result<(), ReplayStoreError> {
+        Err(ReplayStoreError::Io(std::io::Error::other("disk full")))
+    }
+
+    fn list(
+        &self,
+        _filter: &ReplayFilter,
+        _page: usize,
+        _per_page: usize,
+    ) -> Result<ReplayPage, ReplayStoreError> {
+        Err(ReplayStoreError::Io(std::io::Error::other("disk full")))
+    }
+
+    fn get(&self, _id: &Uuid) -> Result<Replay, ReplayStoreError> {
+        Err(ReplayStoreError::UnknownReplay)
+    }
+}
+
+#[tokio::test]
+async fn failed_save_is_reported() {
+    let (server, _dir) = test_server_with_replay_store(Arc::new(FailingReplayStore));
+    let (mut game, colors) = start_game_with_colors(&server).await;
+
+    game.full_move_mister_x(110).await;
+    let _ = game.send_detective_move(&colors[0], 110, "taxi").await;
+    let _ = game.send_detective_move(&colors[1], 107, "bus").await;
+    let _ = game.send_detective_move(&colors[2], 108, "bus").await;
+    let _ = game.send_detective_move(&colors[3], 109, "taxi").await;
+    send_message(&mut game.detective, "submitMove", None).await;
+
+    assert_receive_message::<EndMove>(&mut game.detective, "endMove").await;
+    // the reason stays in the log of the server
+    let error = assert_receive_message::<Error>(&mut game.detective, "error")
+        .await
+        .unwrap();
+    assert_eq!(error.code, "internal");
+    assert_eq!(error.message, "failed to save the replay");
+
+    // the game still ends normally
+    let ended = assert_receive_message::<GameEnded>(&mut game.detective, "gameEnded")
+        .await
+        .unwrap();
+    assert_eq!(ended.winner, "detective");
+    assert_receive_message::<Game>(&mut game.detective, "gameState").await;
+
+    let response = server.get("/game/replays").expect_failure().await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    // the reason of the store is not leaked
+    response.assert_json(&serde_json::json!({ "error": "replay store failed" }));
+}
@@ -0,0 +1,109 @@
+use axum_test::TestWebSocket;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{
+    connection::start_game_with_colors,
+    data::Game,
+    test_server,
+    ws::{assert_receive_message, get_ws_connection},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Ack {
+    id: String,
+    packet: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Error {
+    code: String,
+    id: Option<String>,
+    packet: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GameCreated {}
+
+async fn assert_ack(connection: &mut TestWebSocket, id: &str, packet: &str) {
+    let ack = assert_receive_message::<Ack>(connection, "ack")
+        .await
+        .unwrap();
+    assert_eq!(ack.id, id);
+    assert_eq!(ack.packet, packet);
+}
+
+async fn receive_error(connection: &mut TestWebSocket) -> Error {
+    assert_receive_message::<Error>(connection, "error")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn handled_requests_are_acknowledged() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    player
+        .send_text(format!(
+            "[createGame#create-1] {}",
+            json!({ "number_of_detectives": 4 })
+        ))
+        .await;
+    assert_receive_message::<GameCreated>(&mut player, "game").await;
+    assert_ack(&mut player, "create-1", "createGame").await;
+}
+
+#[tokio::test]
+async fn errors_echo_the_request_id() {
+    let (server, _dir) = test_server();
+    let mut player = get_ws_connection(&server).await;
+
+    player.send_text("[startGame#7]").await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "not_in_lobby");
+    assert_eq!(error.id.as_deref(), Some("7"));
+
+    player.send_text("[fooBar#8] {}").await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.code, "unknown_packet");
+    assert_eq!(error.id.as_deref(), Some("8"));
+    assert_eq!(error.packet.as_deref(), Some("fooBar"));
+
+    player.send_text("[startGame]").await;
+    let error = receive_error(&mut player).await;
+    assert_eq!(error.id, None);
+}
+
+#[tokio::test]
+async fn moves_in_flight_can_be_told_apart() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
+    game.full_move_mister_x(110).await;
+
+    let moves = [
+        (&colors[0], 110, "taxi"),
+        (&colors[1], 117, "taxi"),
+        (&colors[2], 108, "bus"),
+    ];
+    for (id, (color, station, transport_type)) in moves.into_iter().enumerate() {
+        game.detective
+            .send_text(format!(
+                "[moveDetective#{id}] {}",
+                json!({ "color": color, "station_id": station, "transport_type": transport_type })
+            ))
+            .await;
+    }
+
+    assert_receive_message::<Game>(&mut game.detective, "gameState").await;
+    assert_ack(&mut game.detective, "0", "moveDetective").await;
+
+    let error = receive_error(&mut game.detective).await;
+    assert_eq!(error.code, "invalid_move");
+    assert_eq!(error.id.as_deref(), Some("1"));
+
+    assert_receive_message::<Game>(&mut game.detective, "gameState").await;
+    assert_ack(&mut game.detective, "2", "moveDetective").await;
+}
@@ -0,0 +1,190 @@
+use axum_test::{TestServer, http::StatusCode};
+use serde::Deserialize;
+
+use crate::common::{test_server, test_server_with_maps};
+
+mod common;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Step {
+    station: u8,
+    ticket: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Reachable {
+    station: u8,
+    moves: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Connection {
+    from: u8,
+    to: u8,
+    mode: String,
+}
+
+fn step(station: u8, ticket: &str) -> Step {
+    Step {
+        station,
+        ticket: ticket.to_string(),
+    }
+}
+
+async fn get_routes(server: &TestServer, query: &[(&str, &str)]) -> Vec<Vec<Step>> {
+    let mut request = server.get("/map/route");
+    for (name, value) in query {
+        request = request.add_query_param(name, value);
+    }
+
+    request.await.json::<Vec<Vec<Step>>>()
+}
+
+#[tokio::test]
+async fn finds_shortest_routes() {
+    let (server, _dir) = test_server();
+
+    assert_eq!(
+        get_routes(&server, &[("from", "100"), ("to", "120")]).await,
+        vec![vec![step(110, "taxi"), step(120, "bus")]]
+    );
+    assert_eq!(
+        get_routes(&server, &[("from", "103"), ("to", "109")]).await,
+        vec![vec![step(109, "underground")], vec![step(109, "taxi")]]
+    );
+    assert_eq!(
+        get_routes(&server, &[("from", "100"), ("to", "100")]).await,
+        vec![Vec::<Step>::new()]
+    );
+}
+
+#[tokio::test]
+async fn routes_respect_tickets() {
+    let (server, _dir) = test_server();
+
+    assert_eq!(
+        get_routes(
+            &server,
+            &[("from", "103"), ("to", "109"), ("underground", "0")]
+        )
+        .await,
+        vec![vec![step(109, "taxi")]]
+    );
+    assert!(
+        get_routes(&server, &[("from", "100"), ("to", "120"), ("bus", "0")])
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn routes_on_full_map_are_valid() {
+    let (server, _dir) = test_server_with_maps();
+    let connections = server
+        .get("/map/connections")
+        .add_query_param("map", "london-full")
+        .await
+        .json::<Vec<Connection>>();
+
+    let routes = get_routes(
+        &server,
+        &[
+            ("map", "london-full"),
+            ("from", "13"),
+            ("to", "155"),
+            ("underground", "0"),
+        ],
+    )
+    .await;
+
+    assert!(!routes.is_empty());
+    assert!(routes.len() <= 10);
+    for route in &routes {
+        assert_eq!(route.len(), routes[0].len());
+        assert_eq!(route.last().unwrap().station, 155);
+
+        let mut station = 13;
+        for step in route {
+            assert_ne!(step.ticket, "underground");
+            assert!(connections.iter().any(|connection| {
+                connection.mode == step.ticket
+                    && ((connection.from, connection.to) == (station, step.station)
+                        || (connection.to, connection.from) == (station, step.station))
+            }));
+            station = step.station;
+        }
+    }
+}
+
+#[tokio::test]
+async fn lists_reachable_stations() {
+    let (server, _dir) = test_server();
+
+    let reachable = server
+        .get("/map/reachable")
+        .add_query_param("from", 100)
+        .await
+        .json::<Vec<Reachable>>();
+    assert_eq!(
+        reachable,
+        vec![
+            Reachable {
+                station: 106,
+                moves: 1
+            },
+            Reachable {
+                station: 110,
+                moves: 1
+            },
+            Reachable {
+                station: 116,
+                moves: 1
+            },
+            Reachable {
+                station: 104,
+                moves: 2
+            },
+            Reachable {
+                station: 120,
+                moves: 2
+            },
+        ]
+    );
+
+    let reachable = server
+        .get("/map/reachable")
+        .add_query_param("from", 100)
+        .add_query_param("taxi", 1)
+        .add_query_param("bus", 0)
+        .await
+        .json::<Vec<Reachable>>();
+    assert_eq!(
+        reachable.iter().map(|r| r.station).collect::<Vec<_>>(),
+        vec![106, 110, 116]
+    );
+}
+
+#[tokio::test]
+async fn rejects_invalid_route_queries() {
+    let (server, _dir) = test_server();
+
+    server
+        .get("/map/route")
+        .add_query_param("from", 100)
+        .add_query_param("to", 42)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    let response = server
+        .get("/map/route")
+        .add_query_param("from", 100)
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.text().contains("missing field `to`"));
+    server
+        .get("/map/reachable")
+        .add_query_param("from", 100)
+        .add_query_param("map", "paris")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
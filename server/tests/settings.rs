@@ -0,0 +1,208 @@
+use std::{collections::HashMap, net::SocketAddr};
+
+use serde_json::json;
+use server::{
+    Settings,
+    services::data::{DataServiceTrait, service::DataService},
+    settings::{Limits, SettingsError},
+};
+use tempfile::TempDir;
+
+use crate::common::{
+    connection::{create_game, start_game},
+    test_server_with_settings,
+    ws::{assert_receive_error, get_ws_connection, send_message},
+};
+
+mod common;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    move |name| vars.get(name).cloned()
+}
+
+fn load(config: &str, vars: &[(&str, &str)]) -> Result<Settings, SettingsError> {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, config).unwrap();
+
+    Settings::load(path, env(vars))
+}
+
+#[test]
+fn missing_file_uses_defaults() {
+    let dir = TempDir::new().unwrap();
+    let settings = Settings::load(dir.path().join("config.toml"), env(&[])).unwrap();
+
+    assert_eq!(
+        settings.server.address,
+        "0.0.0.0:8081".parse::<SocketAddr>().unwrap()
+    );
+    assert_eq!(settings.rules.rounds, 24);
+    assert_eq!(settings.storage.replay_database, None);
+}
+
+#[test]
+fn reads_config_file() {
+    let settings = load(
+        r#"
+        [server]
+        address = "127.0.0.1:9000"
+        log_level = "debug"
+
+        [storage]
+        replay_database = "replays.db"
+
+        [rules]
+        rounds = 10
+        reveal_rounds = [2, 10]
+        colors = ["red", "blue"]
+
+        [limits]
+        max_lobbies = 3
+        "#,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        settings.server.address,
+        "127.0.0.1:9000".parse::<SocketAddr>().unwrap()
+    );
+    assert_eq!(settings.log_level().unwrap(), tracing::Level::DEBUG);
+    assert_eq!(settings.storage.replay_database, Some("replays.db".into()));
+    assert_eq!(settings.rules.colors, vec!["red", "blue"]);
+    assert_eq!(settings.limits.max_lobbies, 3);
+    assert_eq!(
+        settings.limits.max_spectators,
+        Limits::default().max_spectators
+    );
+}
+
+#[test]
+fn environment_overrides_config_file() {
+    let settings = load(
+        r#"
+        [server]
+        address = "127.0.0.1:9000"
+
+        [limits]
+        max_lobbies = 3
+        "#,
+        &[
+            ("PORT", "9001"),
+            ("DATA_DIR", "/tmp/data"),
+            ("MAX_LOBBIES", "5"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        settings.server.address,
+        "127.0.0.1:9001".parse::<SocketAddr>().unwrap()
+    );
+    assert_eq!(settings.storage.data_dir, std::path::Path::new("/tmp/data"));
+    assert_eq!(settings.limits.max_lobbies, 5);
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    assert!(matches!(
+        load("[server]\nport = 80", &[]),
+        Err(SettingsError::InvalidFile(_))
+    ));
+    assert_eq!(
+        load("", &[("PORT", "http")]).unwrap_err(),
+        SettingsError::InvalidEnv("PORT", "http".to_string())
+    );
+    assert_eq!(
+        load("", &[("LOG_LEVEL", "loud")]).unwrap_err(),
+        SettingsError::InvalidLogLevel("loud".to_string())
+    );
+    assert_eq!(
+        load("[rules]\nrounds = 0", &[]).unwrap_err().to_string(),
+        "invalid rules: rules.rounds: there has to be at least one round"
+    );
+    assert_eq!(
+        load("[rules]\nrounds = 5\nreveal_rounds = [3, 8]", &[])
+            .unwrap_err()
+            .to_string(),
+        "invalid rules: rules.reveal_rounds[1]: round 8 does not exist"
+    );
+    assert!(matches!(
+        load("[rules]\ncolors = [\"red\", \"red\"]", &[]),
+        Err(SettingsError::InvalidRules(_))
+    ));
+    assert_eq!(
+        load("[limits]\nmax_lobbies = 0", &[]).unwrap_err(),
+        SettingsError::InvalidLimit("max_lobbies")
+    );
+    assert_eq!(
+        load("[limits]\nmax_spectators = 0", &[]).unwrap_err(),
+        SettingsError::InvalidLimit("max_spectators")
+    );
+}
+
+#[test]
+fn rules_configure_rounds_and_colors() {
+    let settings = load(
+        "[rules]\nrounds = 4\nreveal_rounds = [2]\ncolors = [\"red\", \"blue\"]",
+        &[],
+    )
+    .unwrap();
+    let data_service = DataService::london(settings.rules);
+
+    let rounds = data_service.get_all_rounds();
+    assert_eq!(rounds.len(), 4);
+    assert!(rounds[1].show_mister_x);
+    assert!(!rounds[3].show_mister_x);
+    assert_eq!(data_service.get_colors(), vec!["red", "blue"]);
+}
+
+#[tokio::test]
+async fn limits_number_of_lobbies() {
+    let mut settings = Settings::default();
+    settings.limits.max_lobbies = 1;
+    let (server, _dir) = test_server_with_settings(settings);
+
+    let mut first = get_ws_connection(&server).await;
+    create_game(&mut first).await;
+
+    let mut second = get_ws_connection(&server).await;
+    send_message(
+        &mut second,
+        "createGame",
+        Some(json!({ "number_of_detectives": 4 })),
+    )
+    .await;
+    assert_receive_error(&mut second, "too many open lobbies").await;
+}
+
+#[tokio::test]
+async fn limits_number_of_spectators() {
+    let mut settings = Settings::default();
+    settings.limits.max_spectators = 1;
+    let (server, _dir) = test_server_with_settings(settings);
+    let game = start_game(&server).await;
+
+    let mut first = get_ws_connection(&server).await;
+    send_message(
+        &mut first,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "detective" })),
+    )
+    .await;
+
+    let mut second = get_ws_connection(&server).await;
+    send_message(
+        &mut second,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "detective" })),
+    )
+    .await;
+    assert_receive_error(&mut second, "game has too many spectators").await;
+}
@@ -4,8 +4,8 @@ mod common;
 
 #[tokio::test]
 async fn shows_mister_x() {
-    let (mut server, _dir) = test_server();
-    let (mut game, colors) = start_game_with_colors(&mut server).await;
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors(&server).await;
 
     let state = game.full_move_mister_x(110).await;
     assert!(state.mister_x.station_id.is_none());
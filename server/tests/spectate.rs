@@ -0,0 +1,183 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::common::{
+    connection::{start_game, start_game_with_colors_and_settings, start_game_with_settings},
+    data::Game,
+    test_server,
+    ws::{assert_receive_error, assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Spectating {
+    view: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+#[derive(Debug, Deserialize)]
+struct StartMove {
+    role: String,
+}
+
+#[tokio::test]
+async fn detective_view_hides_mister_x() {
+    let (server, _dir) = test_server();
+    let mut game = start_game(&server).await;
+    game.receive_start_move_message("mister_x").await;
+
+    let mut spectator = get_ws_connection(&server).await;
+    send_message(
+        &mut spectator,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "detective" })),
+    )
+    .await;
+
+    let message = assert_receive_message::<Spectating>(&mut spectator, "spectating").await;
+    assert_eq!(message.unwrap().view, "detective");
+
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert!(state.mister_x.station_id.is_none());
+    assert_eq!(state.players.len(), 4);
+
+    assert_receive_message::<Game>(&mut game.mister_x, "gameState").await;
+    assert_receive_message::<Game>(&mut game.detective, "gameState").await;
+    game.full_move_mister_x(110).await;
+
+    assert_receive_message::<EndMove>(&mut spectator, "endMove").await;
+    let message = assert_receive_message::<StartMove>(&mut spectator, "startMove").await;
+    assert_eq!(message.unwrap().role, "detective");
+
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert!(state.mister_x.station_id.is_none());
+}
+
+#[tokio::test]
+async fn omniscient_view_is_disabled_by_default() {
+    let (server, _dir) = test_server();
+    let game = start_game(&server).await;
+
+    let mut spectator = get_ws_connection(&server).await;
+    send_message(
+        &mut spectator,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "omniscient" })),
+    )
+    .await;
+
+    assert_receive_error(&mut spectator, "omniscient spectators are disabled").await;
+}
+
+#[tokio::test]
+async fn omniscient_view_shows_mister_x() {
+    let (server, _dir) = test_server();
+    let mut game = start_game_with_settings(
+        &server,
+        json!({
+            "number_of_detectives": 4,
+            "spectators": { "allow_omniscient": true, "omniscient_delay": 0 },
+        }),
+    )
+    .await;
+
+    let mut spectator = get_ws_connection(&server).await;
+    send_message(
+        &mut spectator,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "omniscient" })),
+    )
+    .await;
+
+    assert_receive_message::<Spectating>(&mut spectator, "spectating").await;
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert_eq!(state.mister_x.station_id, Some(104));
+
+    game.receive_start_move_message("mister_x").await;
+    assert_receive_message::<Game>(&mut game.mister_x, "gameState").await;
+    assert_receive_message::<Game>(&mut game.detective, "gameState").await;
+    game.full_move_mister_x(110).await;
+
+    assert_receive_message::<EndMove>(&mut spectator, "endMove").await;
+    assert_receive_message::<StartMove>(&mut spectator, "startMove").await;
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert_eq!(state.mister_x.station_id, Some(110));
+}
+
+#[tokio::test]
+async fn omniscient_view_can_be_delayed() {
+    let (server, _dir) = test_server();
+    let (mut game, colors) = start_game_with_colors_and_settings(
+        &server,
+        json!({
+            "number_of_detectives": 4,
+            "spectators": { "allow_omniscient": true, "omniscient_delay": 1 },
+        }),
+    )
+    .await;
+
+    let mut spectator = get_ws_connection(&server).await;
+    send_message(
+        &mut spectator,
+        "spectateGame",
+        Some(json!({ "id": game.id, "view": "omniscient" })),
+    )
+    .await;
+
+    assert_receive_message::<Spectating>(&mut spectator, "spectating").await;
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert!(state.mister_x.station_id.is_none());
+
+    game.full_move_mister_x(110).await;
+    assert_receive_message::<EndMove>(&mut spectator, "endMove").await;
+    assert_receive_message::<StartMove>(&mut spectator, "startMove").await;
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert!(state.mister_x.station_id.is_none());
+
+    game.full_move_detectives(
+        &colors,
+        &[106, 107, 108, 109],
+        &["taxi", "bus", "bus", "taxi"],
+    )
+    .await;
+    for _ in 0..4 {
+        assert_receive_message::<Game>(&mut spectator, "gameState").await;
+    }
+    assert_receive_message::<EndMove>(&mut spectator, "endMove").await;
+    assert_receive_message::<StartMove>(&mut spectator, "startMove").await;
+    let state = assert_receive_message::<Game>(&mut spectator, "gameState")
+        .await
+        .unwrap();
+    assert_eq!(state.round, 1);
+    assert_eq!(state.mister_x.station_id, Some(110));
+}
+
+#[tokio::test]
+async fn can_not_spectate_unknown_game() {
+    let (server, _dir) = test_server();
+
+    let mut spectator = get_ws_connection(&server).await;
+    send_message(
+        &mut spectator,
+        "spectateGame",
+        Some(json!({ "id": "fffdc005-f76c-49d1-b39a-cbbb801eaece", "view": "detective" })),
+    )
+    .await;
+
+    assert_receive_error(&mut spectator, "unknown game").await;
+}
@@ -6,8 +6,8 @@ mod common;
 
 #[tokio::test]
 async fn correctly_starts_round() {
-    let (mut server, _dir) = test_server();
-    let mut game = start_game(&mut server).await;
+    let (server, _dir) = test_server();
+    let mut game = start_game(&server).await;
 
     #[derive(Debug, Deserialize)]
     struct StartMove {
@@ -0,0 +1,152 @@
+use axum_test::{TestServer, TestWebSocket};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::common::{
+    connection::create_game,
+    test_server,
+    ws::{assert_receive_message, get_ws_connection, send_message},
+};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct GameStarted {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StartMove {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EndMove;
+
+struct Players {
+    mister_x: TestWebSocket,
+    detective: TestWebSocket,
+}
+
+/// starts a game in which both players asked for state deltas
+async fn start_game_with_deltas(server: &TestServer) -> Players {
+    let mut player_1 = get_ws_connection(server).await;
+    let mut player_2 = get_ws_connection(server).await;
+
+    let id = create_game(&mut player_1).await;
+    for player in [&mut player_1, &mut player_2] {
+        send_message(
+            player,
+            "joinGame",
+            Some(json!({ "id": id, "state_deltas": true })),
+        )
+        .await;
+    }
+    send_message(&mut player_2, "startGame", None).await;
+
+    let started = assert_receive_message::<GameStarted>(&mut player_1, "gameStarted")
+        .await
+        .unwrap();
+    assert_receive_message::<GameStarted>(&mut player_2, "gameStarted").await;
+
+    if started.role == "mister_x" {
+        Players {
+            mister_x: player_1,
+            detective: player_2,
+        }
+    } else {
+        Players {
+            mister_x: player_2,
+            detective: player_1,
+        }
+    }
+}
+
+/// full state that comes with every turn
+async fn assert_start_move(player: &mut TestWebSocket, role: &str) -> Value {
+    let start_move = assert_receive_message::<StartMove>(player, "startMove")
+        .await
+        .unwrap();
+    assert_eq!(start_move.role, role);
+    assert_receive_message::<Value>(player, "gameState")
+        .await
+        .unwrap()
+}
+
+/// plays the first move of Mister X, returns the state the detective starts from
+async fn detectives_turn(players: &mut Players) -> Value {
+    assert_start_move(&mut players.mister_x, "mister_x").await;
+    assert_start_move(&mut players.detective, "mister_x").await;
+
+    send_message(
+        &mut players.mister_x,
+        "moveMisterX",
+        Some(json!([{ "station_id": 110, "transport_type": "taxi" }])),
+    )
+    .await;
+    send_message(&mut players.mister_x, "submitMove", None).await;
+    assert_receive_message::<EndMove>(&mut players.mister_x, "endMove").await;
+    assert_receive_message::<EndMove>(&mut players.detective, "endMove").await;
+
+    assert_start_move(&mut players.mister_x, "detective").await;
+    assert_start_move(&mut players.detective, "detective").await
+}
+
+#[tokio::test]
+async fn detective_moves_are_sent_as_deltas() {
+    let (server, _dir) = test_server();
+    let mut players = start_game_with_deltas(&server).await;
+    let state = detectives_turn(&mut players).await;
+    let seq = state["seq"].as_u64().unwrap();
+
+    send_message(
+        &mut players.detective,
+        "moveDetective",
+        Some(json!({ "color": "red", "station_id": 106, "transport_type": "taxi" })),
+    )
+    .await;
+
+    for player in [&mut players.mister_x, &mut players.detective] {
+        let delta = assert_receive_message::<Value>(player, "gameStateDelta")
+            .await
+            .unwrap();
+        assert_eq!(
+            delta,
+            json!({
+                "seq": seq + 1,
+                "changes": [
+                    { "type": "piece_moved", "color": "red", "station_id": 106 },
+                    {
+                        "type": "tickets_changed",
+                        "color": "red",
+                        "available_transport": { "taxi": 9, "bus": 8, "underground": 4 },
+                    },
+                ],
+            })
+        );
+    }
+}
+
+#[tokio::test]
+async fn resync_sends_the_full_state() {
+    let (server, _dir) = test_server();
+    let mut players = start_game_with_deltas(&server).await;
+    let state = detectives_turn(&mut players).await;
+    assert_eq!(state["mister_x"]["station_id"], Value::Null);
+
+    send_message(
+        &mut players.detective,
+        "moveDetective",
+        Some(json!({ "color": "red", "station_id": 106, "transport_type": "taxi" })),
+    )
+    .await;
+    assert_receive_message::<Value>(&mut players.detective, "gameStateDelta").await;
+
+    send_message(&mut players.detective, "resync", None).await;
+    let resynced = assert_receive_message::<Value>(&mut players.detective, "gameState")
+        .await
+        .unwrap();
+    assert_eq!(resynced["seq"], state["seq"].as_u64().unwrap() + 1);
+    assert_eq!(resynced["players"][0]["station_id"], 106);
+    assert_eq!(resynced["mister_x"]["station_id"], Value::Null);
+}
@@ -0,0 +1,160 @@
+use axum_test::{TestServer, http::StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::common::{data::Game, test_server};
+
+mod common;
+
+#[derive(Debug, Deserialize)]
+struct Validation {
+    legal: bool,
+    reason: Option<String>,
+    snapshot: Value,
+    state: Game,
+}
+
+fn snapshot(active_role: &str, game_round: u8, detective_actions: Value) -> Value {
+    json!({
+        "active_role": active_role,
+        "game_round": game_round,
+        "detectives": [
+            { "color": "red", "start_station_id": 100, "actions": [] },
+            { "color": "blue", "start_station_id": 103, "actions": detective_actions },
+        ],
+        "mister_x": { "start_station_id": 104, "actions": [] },
+    })
+}
+
+async fn validate(server: &TestServer, snapshot: &Value, proposed_move: Value) -> Validation {
+    server
+        .post("/game/validate")
+        .json(&json!({ "snapshot": snapshot, "move": proposed_move }))
+        .await
+        .json::<Validation>()
+}
+
+#[tokio::test]
+async fn legal_move_returns_resulting_state() {
+    let (server, _dir) = test_server();
+    let before = snapshot("mister_x", 0, json!([]));
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "mister_x": [{ "station_id": 110, "transport_type": "taxi" }] }),
+    )
+    .await;
+
+    assert!(validation.legal);
+    assert_eq!(validation.reason, None);
+    assert_eq!(validation.state.mister_x.station_id, Some(110));
+    assert_eq!(
+        validation.snapshot["mister_x"]["actions"],
+        json!([{ "single": { "station": 110, "action_type": "taxi" } }])
+    );
+}
+
+#[tokio::test]
+async fn illegal_moves_return_reason_and_unchanged_state() {
+    let (server, _dir) = test_server();
+    let before = snapshot("mister_x", 0, json!([]));
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "mister_x": [{ "station_id": 106, "transport_type": "taxi" }] }),
+    )
+    .await;
+    assert!(!validation.legal);
+    assert_eq!(validation.reason.as_deref(), Some("no_connection"));
+    assert_eq!(validation.snapshot, before);
+    assert_eq!(validation.state.mister_x.station_id, Some(104));
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "detective": { "color": "red", "station_id": 110, "transport_type": "taxi" } }),
+    )
+    .await;
+    assert_eq!(validation.reason.as_deref(), Some("not_your_turn"));
+
+    let validation = validate(&server, &before, json!({ "mister_x": [] })).await;
+    assert_eq!(validation.reason.as_deref(), Some("wrong_number_of_moves"));
+}
+
+#[tokio::test]
+async fn detective_moves_are_checked() {
+    let (server, _dir) = test_server();
+    let used_underground = json!([
+        { "station": 109, "action_type": "underground" },
+        { "station": 103, "action_type": "underground" },
+        { "station": 109, "action_type": "underground" },
+        { "station": 103, "action_type": "underground" },
+    ]);
+    let before = snapshot("detective", 4, used_underground);
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "detective": { "color": "blue", "station_id": 109, "transport_type": "underground" } }),
+    )
+    .await;
+    assert_eq!(validation.reason.as_deref(), Some("no_tickets_left"));
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "detective": { "color": "blue", "station_id": 109, "transport_type": "taxi" } }),
+    )
+    .await;
+    assert!(validation.legal);
+    assert_eq!(validation.state.players[1].station_id, 109);
+    assert_eq!(validation.state.players[1].available_transport.taxi, 9);
+
+    let validation = validate(
+        &server,
+        &before,
+        json!({ "detective": { "color": "pink", "station_id": 109, "transport_type": "taxi" } }),
+    )
+    .await;
+    assert_eq!(validation.reason.as_deref(), Some("unknown_detective"));
+}
+
+#[tokio::test]
+async fn validation_uses_the_map() {
+    let (server, _dir) = test_server();
+
+    server
+        .post("/game/validate")
+        .json(&json!({
+            "map": "paris",
+            "snapshot": snapshot("mister_x", 0, json!([])),
+            "move": { "mister_x": [{ "station_id": 110, "transport_type": "taxi" }] },
+        }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn snapshots_longer_than_the_map_are_rejected() {
+    let (server, _dir) = test_server();
+    let taxi_rides: Vec<_> = (0..300)
+        .map(|i| json!({ "station": if i % 2 == 0 { 106 } else { 100 }, "action_type": "taxi" }))
+        .collect();
+
+    for snapshot in [
+        snapshot("mister_x", 7, json!([])),
+        snapshot("detective", 4, json!(taxi_rides)),
+    ] {
+        server
+            .post("/game/validate")
+            .json(&json!({
+                "snapshot": snapshot,
+                "move": { "mister_x": [{ "station_id": 110, "transport_type": "taxi" }] },
+            }))
+            .expect_failure()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,4 +1,4 @@
-import type { Connection, Station } from "~/utils/type/canvas";
+import type { Connection, Station } from "~/utils/type/packets";
 
 const MODE_DATA: Record<
 	"taxi" | "bus" | "underground" | "water",
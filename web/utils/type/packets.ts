@@ -0,0 +1,218 @@
+// generated by `cargo run -p packets --bin typescript`, do not edit
+
+export const PROTOCOL_VERSION = 3;
+
+/**
+ * sent for every client packet with a request id which was handled without an error
+ */
+export type AckPacket = { id: string, packet: string, };
+
+export type AddBotPacket = { name: string, };
+
+export type Connection = { from: number, to: number, mode: StationType, };
+
+export type CreateGamePacket = { number_of_detectives: number, spectators?: SpectatorSettings, 
+/**
+ * public lobbies are listed by `GET /game/lobbies`
+ */
+public?: boolean, 
+/**
+ * id of a map listed by `GET /map`, the default map if not set
+ */
+map?: string, };
+
+export type DetectiveActionType = "taxi" | "bus" | "underground";
+
+export type DetectiveData = { color: string, station_id: number, available_transport: DetectiveTransportData, };
+
+/**
+ * a move the active side can make, the same as the `moveDetective` packet
+ */
+export type DetectiveMove = { color: string, station_id: number, transport_type: DetectiveActionType, };
+
+export type DetectiveTransportData = { taxi: number, bus: number, underground: number, };
+
+/**
+ * how packets are sent on a connection, negotiated with `hello`
+ */
+export type Encoding = "text" | "message_pack" | "cbor";
+
+/**
+ * stable identifier of an error, clients should match on it instead of the message
+ */
+export type ErrorCode = "unknown_packet" | "invalid_packet" | "unsupported_protocol_version" | "game_already_joined" | "not_in_game" | "not_in_lobby" | "unknown_bot" | "no_rematch" | "unknown_session" | "unknown_lobby" | "reserved_for_rematch" | "lobby_full" | "invalid_number_of_detectives" | "not_enough_players" | "too_many_lobbies" | "unknown_map" | "unknown_game" | "not_your_turn" | "wrong_role" | "omniscient_spectators_disabled" | "too_many_spectators" | "invalid_move" | "not_all_moved" | "internal";
+
+export type ErrorPacket = { code: ErrorCode, message: string, 
+/**
+ * more information for some codes, e.g. the reason of an invalid move
+ */
+details?: JsonValue, 
+/**
+ * name of the client packet which caused the error
+ */
+packet?: string, 
+/**
+ * request id of the client packet which caused the error
+ */
+id?: string, };
+
+export type GameEndedPacket = { winner: Role, };
+
+export type GamePacket = { id: string, code: string, };
+
+export type GameStartedPacket = { role: Role, 
+/**
+ * secret to resume the game with after reconnecting
+ */
+session: string, };
+
+export type GameState = { players: Array<DetectiveData>, mister_x: MisterXData, round: number, 
+/**
+ * counts the states sent in this game, a delta has the `seq` of the state it leads to
+ */
+seq: number, };
+
+/**
+ * changes from the last state sent to the next one
+ */
+export type GameStateDelta = { seq: number, changes: Array<StateChange>, };
+
+/**
+ * first packet of a client, connections without it use version 1
+ */
+export type HelloPacket = { protocol_version: number, client_name?: string, 
+/**
+ * the server sends `welcome` and all further packets with it
+ */
+encoding?: Encoding, };
+
+export type JoinGamePacket = { id: string, 
+/**
+ * sends `LegalMoves` after every `StartMove` of the own side
+ */
+legal_moves?: boolean, 
+/**
+ * sends `GameStateDelta` instead of `GameState` for tentative moves
+ */
+state_deltas?: boolean, };
+
+export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
+
+/**
+ * every move of the active side at the start of its turn
+ */
+export type LegalMoves = { "role": "detective", "moves": Array<DetectiveMove> } | { "role": "mister_x", "moves": Array<MisterXMove> };
+
+export type LobbyPlayerData = { id: string, score: number, accepted_rematch: boolean, };
+
+export type LobbyStatePacket = { id: string, players: Array<LobbyPlayerData>, };
+
+export type MisterXAbilityData = { double_move: number, hidden: number, };
+
+export type MisterXActionType = "taxi" | "bus" | "underground" | "hidden";
+
+export type MisterXData = { station_id: number | null, abilities: MisterXAbilityData, moves: Array<MisterXActionType>, };
+
+/**
+ * a single move Mister X can make, two of them make a double move
+ */
+export type MisterXMove = { station_id: number, transport_type: MisterXActionType, };
+
+export type MoveDetectivePacket = { color: string, station_id: number, transport_type: DetectiveActionType, };
+
+export type MoveMisterXPacket = { station_id: number, transport_type: MisterXActionType, };
+
+export type Replay = { mister_x_starting_station: number, detective_starting_stations: Record<string, number>, actions: Array<ReplayAction>, winner: Role, 
+/**
+ * set by the server, the game itself doesn't know which map it is played on
+ */
+map: string | null, };
+
+export type ReplayAction = { "type": "detective", color: string, station: number, action_type: DetectiveActionType, } | { "type": "mister_x", station: number, action_type: MisterXActionType, };
+
+export type ResumeGamePacket = { session: string, };
+
+export type Role = "detective" | "mister_x";
+
+export type SpectateGamePacket = { id: string, view: SpectatorView, };
+
+export type SpectatingPacket = { view: SpectatorView, };
+
+export type SpectatorSettings = { 
+/**
+ * whether spectators may watch with Mister X visible
+ */
+allow_omniscient: boolean, 
+/**
+ * number of rounds the omniscient view of Mister X lags behind
+ */
+omniscient_delay: number, };
+
+export type SpectatorView = "detective" | "omniscient";
+
+export type StartMovePacket = { role: Role, };
+
+export type StateChange = { "type": "piece_moved", color: string, station_id: number, } | { "type": "tickets_changed", color: string, available_transport: DetectiveTransportData, } | { "type": "round_advanced", round: number, };
+
+export type Station = { id: number, pos_x: number, pos_y: number, types: Array<StationType>, name?: string | null, };
+
+export type StationType = "taxi" | "bus" | "underground" | "water";
+
+export type WelcomePacket = { 
+/**
+ * version used on this connection
+ */
+protocol_version: number, 
+/**
+ * newest version the server speaks
+ */
+server_protocol_version: number, encoding: Encoding, };
+
+export type ServerPacket =
+	| { name: "error"; content: ErrorPacket }
+	| { name: "ack"; content: AckPacket }
+	| { name: "welcome"; content: WelcomePacket }
+	| { name: "game"; content: GamePacket }
+	| { name: "lobbyState"; content: LobbyStatePacket }
+	| { name: "gameStarted"; content: GameStartedPacket }
+	| { name: "spectating"; content: SpectatingPacket }
+	| { name: "startMove"; content: StartMovePacket }
+	| { name: "legalMoves"; content: LegalMoves }
+	| { name: "gameState"; content: GameState }
+	| { name: "gameStateDelta"; content: GameStateDelta }
+	| { name: "endMove" }
+	| { name: "gameEnded"; content: GameEndedPacket };
+
+export type ClientPacket =
+	| { name: "hello"; content: HelloPacket }
+	| { name: "createGame"; content: CreateGamePacket }
+	| { name: "joinGame"; content: JoinGamePacket }
+	| { name: "spectateGame"; content: SpectateGamePacket }
+	| { name: "resumeGame"; content: ResumeGamePacket }
+	| { name: "addBot"; content: AddBotPacket }
+	| { name: "startGame" }
+	| { name: "moveMisterX"; content: Array<MoveMisterXPacket> }
+	| { name: "moveDetective"; content: MoveDetectivePacket }
+	| { name: "submitMove" }
+	| { name: "rematch" }
+	| { name: "resync" };
+
+/** sends `[name] content`, the request id comes back in `ack` and `error` */
+export function encodePacket(packet: ClientPacket, id?: string): string {
+	const name = id === undefined ? packet.name : `${packet.name}#${id}`;
+	return "content" in packet
+		? `[${name}] ${JSON.stringify(packet.content)}`
+		: `[${name}]`;
+}
+
+/** parses a text frame sent by the server */
+export function decodePacket(message: string): ServerPacket {
+	const space = message.indexOf(" ");
+	if (space === -1) {
+		return { name: message.slice(1, -1) } as ServerPacket;
+	}
+	return {
+		name: message.slice(1, space - 1),
+		content: JSON.parse(message.slice(space + 1)),
+	} as ServerPacket;
+}